@@ -106,12 +106,16 @@ function compile_rocksdb() {
         return
     fi
 
-    version=7063db3f92749eb0601def764c3fb8652be2cd28
-    vernum=7063db3f92749eb0601def764c3fb8652be2cd28
+    version=9.10.0
+    vernum=9.10.0
+    # The commit the v9.10.0 tag points to, checked after the clone.
+    commit=
+    [[ -n "$commit" ]] || error the commit of rocksdb v$version is not pinned.
     echo building rocksdb-$version
     rm -rf rocksdb-$vernum
-    download https://github.com/facebook/rocksdb/archive/$version.tar.gz rocksdb-$version.tar.gz 3c33436e389aad2d8e8e209d1be21073
-    tar xf rocksdb-$version.tar.gz
+    git clone --depth 1 --branch v$version https://github.com/facebook/rocksdb.git rocksdb-$vernum
+    head=`git -C rocksdb-$vernum rev-parse HEAD`
+    [[ "$head" == "$commit" ]] || error rocksdb-$version: commit not correct, expect $commit, got $head
     wd=`pwd`
     cd rocksdb-$vernum
     export EXTRA_CFLAGS="-fPIC -I${wd}/zlib-1.2.8 -I${wd}/bzip2-1.0.6 -I${wd}/snappy-1.1.1 -I${wd}/lz4-r131/lib"
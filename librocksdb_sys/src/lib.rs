@@ -17,7 +17,7 @@ extern crate libc;
 #[cfg(test)]
 extern crate tempdir;
 
-use libc::{c_char, c_uchar, c_int, c_void, size_t, uint64_t};
+use libc::{c_char, c_uchar, c_int, c_uint, c_double, c_void, size_t, uint64_t};
 use std::ffi::CStr;
 
 pub enum DBOptions {}
@@ -36,9 +36,18 @@ pub enum DBComparator {}
 pub enum DBFlushOptions {}
 pub enum DBCompactionFilter {}
 pub enum DBRateLimiter {}
+pub enum DBEnv {}
+pub enum DBPath {}
+pub enum DBLogger {}
+pub enum DBMemoryConsumers {}
+pub enum DBMemoryUsage {}
+pub enum DBEnvOptions {}
+pub enum DBSstFileWriter {}
+pub enum DBIngestExternalFileOptions {}
+pub enum DBHistogramData {}
 
 pub fn new_bloom_filter(bits: c_int) -> *mut DBFilterPolicy {
-    unsafe { rocksdb_filterpolicy_create_bloom(bits) }
+    unsafe { rocksdb_filterpolicy_create_bloom(bits as c_double) }
 }
 
 pub fn new_cache(capacity: size_t) -> *mut DBCache {
@@ -78,6 +87,159 @@ pub enum DBRecoveryMode {
     SkipAnyCorruptedRecords = 3,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C)]
+pub enum DBInfoLogLevel {
+    Debug = 0,
+    Info = 1,
+    Warn = 2,
+    Error = 3,
+    Fatal = 4,
+    Header = 5,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C)]
+pub enum DBRateLimiterMode {
+    ReadsOnly = 0,
+    WritesOnly = 1,
+    AllIo = 2,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C)]
+pub enum DBChecksumType {
+    NoChecksum = 0,
+    CRC32c = 1,
+    XxHash = 2,
+    XxHash64 = 3,
+    XXH3 = 4,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C)]
+pub enum DBDataBlockIndexType {
+    BinarySearch = 0,
+    BinaryAndHash = 1,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C)]
+pub enum DBPrepopulateBlockCache {
+    Disabled = 0,
+    FlushOnly = 1,
+}
+
+/// The histograms of the statistics, the values are the ones of
+/// `rocksdb::Histograms` in RocksDB 9.10.0.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C)]
+pub enum DBStatisticsHistogramType {
+    DbGet = 0,
+    DbWrite = 1,
+    CompactionTime = 2,
+    CompactionCpuTime = 3,
+    SubcompactionSetupTime = 4,
+    TableSyncMicros = 5,
+    CompactionOutfileSyncMicros = 6,
+    WalFileSyncMicros = 7,
+    ManifestFileSyncMicros = 8,
+    TableOpenIoMicros = 9,
+    DbMultiget = 10,
+    ReadBlockCompactionMicros = 11,
+    ReadBlockGetMicros = 12,
+    WriteRawBlockMicros = 13,
+    NumFilesInSingleCompaction = 14,
+    DbSeek = 15,
+    WriteStall = 16,
+}
+
+impl DBStatisticsHistogramType {
+    /// The name of the histogram in the statistics string.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            DBStatisticsHistogramType::DbGet => "rocksdb.db.get.micros",
+            DBStatisticsHistogramType::DbWrite => "rocksdb.db.write.micros",
+            DBStatisticsHistogramType::CompactionTime => "rocksdb.compaction.times.micros",
+            DBStatisticsHistogramType::CompactionCpuTime => "rocksdb.compaction.times.cpu_micros",
+            DBStatisticsHistogramType::SubcompactionSetupTime => {
+                "rocksdb.subcompaction.setup.times.micros"
+            }
+            DBStatisticsHistogramType::TableSyncMicros => "rocksdb.table.sync.micros",
+            DBStatisticsHistogramType::CompactionOutfileSyncMicros => {
+                "rocksdb.compaction.outfile.sync.micros"
+            }
+            DBStatisticsHistogramType::WalFileSyncMicros => "rocksdb.wal.file.sync.micros",
+            DBStatisticsHistogramType::ManifestFileSyncMicros => {
+                "rocksdb.manifest.file.sync.micros"
+            }
+            DBStatisticsHistogramType::TableOpenIoMicros => "rocksdb.table.open.io.micros",
+            DBStatisticsHistogramType::DbMultiget => "rocksdb.db.multiget.micros",
+            DBStatisticsHistogramType::ReadBlockCompactionMicros => {
+                "rocksdb.read.block.compaction.micros"
+            }
+            DBStatisticsHistogramType::ReadBlockGetMicros => "rocksdb.read.block.get.micros",
+            DBStatisticsHistogramType::WriteRawBlockMicros => "rocksdb.write.raw.block.micros",
+            DBStatisticsHistogramType::NumFilesInSingleCompaction => {
+                "rocksdb.numfiles.in.singlecompaction"
+            }
+            DBStatisticsHistogramType::DbSeek => "rocksdb.db.seek.micros",
+            DBStatisticsHistogramType::WriteStall => "rocksdb.db.write.stall",
+        }
+    }
+}
+
+/// The tickers of the statistics, they are looked up by name in the
+/// statistics string since `rocksdb::Tickers` is renumbered between
+/// RocksDB releases.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DBStatisticsTickerType {
+    BlockCacheMiss,
+    BlockCacheHit,
+    BlockCacheAdd,
+    BlockCacheDataMiss,
+    BlockCacheDataHit,
+    BlockCacheDataAdd,
+    BlockCacheBytesRead,
+    BlockCacheBytesWrite,
+    BloomFilterUseful,
+    MemtableHit,
+    MemtableMiss,
+    NumberKeysWritten,
+    NumberKeysRead,
+    BytesWritten,
+    BytesRead,
+    ReadAmpEstimateUsefulBytes,
+    ReadAmpTotalReadBytes,
+}
+
+impl DBStatisticsTickerType {
+    /// The name of the ticker in the statistics string.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            DBStatisticsTickerType::BlockCacheMiss => "rocksdb.block.cache.miss",
+            DBStatisticsTickerType::BlockCacheHit => "rocksdb.block.cache.hit",
+            DBStatisticsTickerType::BlockCacheAdd => "rocksdb.block.cache.add",
+            DBStatisticsTickerType::BlockCacheDataMiss => "rocksdb.block.cache.data.miss",
+            DBStatisticsTickerType::BlockCacheDataHit => "rocksdb.block.cache.data.hit",
+            DBStatisticsTickerType::BlockCacheDataAdd => "rocksdb.block.cache.data.add",
+            DBStatisticsTickerType::BlockCacheBytesRead => "rocksdb.block.cache.bytes.read",
+            DBStatisticsTickerType::BlockCacheBytesWrite => "rocksdb.block.cache.bytes.write",
+            DBStatisticsTickerType::BloomFilterUseful => "rocksdb.bloom.filter.useful",
+            DBStatisticsTickerType::MemtableHit => "rocksdb.memtable.hit",
+            DBStatisticsTickerType::MemtableMiss => "rocksdb.memtable.miss",
+            DBStatisticsTickerType::NumberKeysWritten => "rocksdb.number.keys.written",
+            DBStatisticsTickerType::NumberKeysRead => "rocksdb.number.keys.read",
+            DBStatisticsTickerType::BytesWritten => "rocksdb.bytes.written",
+            DBStatisticsTickerType::BytesRead => "rocksdb.bytes.read",
+            DBStatisticsTickerType::ReadAmpEstimateUsefulBytes => {
+                "rocksdb.read.amp.estimate.useful.bytes"
+            }
+            DBStatisticsTickerType::ReadAmpTotalReadBytes => "rocksdb.read.amp.total.read.bytes",
+        }
+    }
+}
+
 pub fn error_message(ptr: *mut c_char) -> String {
     let c_str = unsafe { CStr::from_ptr(ptr) };
     let s = format!("{}", c_str.to_string_lossy());
@@ -119,8 +281,30 @@ extern "C" {
     pub fn rocksdb_block_based_options_set_block_restart_interval(
         block_options: *mut DBBlockBasedTableOptions,
         block_restart_interval: c_int);
+    pub fn rocksdb_block_based_options_set_index_block_restart_interval(
+        block_options: *mut DBBlockBasedTableOptions,
+        index_block_restart_interval: c_int);
+    pub fn rocksdb_block_based_options_set_format_version(
+        block_options: *mut DBBlockBasedTableOptions,
+        format_version: c_int);
+    pub fn rocksdb_block_based_options_set_data_block_index_type(
+        block_options: *mut DBBlockBasedTableOptions,
+        index_type: DBDataBlockIndexType);
+    pub fn rocksdb_block_based_options_set_data_block_hash_ratio(
+        block_options: *mut DBBlockBasedTableOptions,
+        ratio: c_double);
+    pub fn rocksdb_block_based_options_set_prepopulate_block_cache(
+        block_options: *mut DBBlockBasedTableOptions,
+        v: DBPrepopulateBlockCache);
+    pub fn rocksdb_block_based_options_set_checksum(
+        block_options: *mut DBBlockBasedTableOptions,
+        checksum: c_char);
     pub fn rocksdb_block_based_options_set_cache_index_and_filter_blocks(
         block_options: *mut DBBlockBasedTableOptions, v: c_uchar);
+    pub fn rocksdb_block_based_options_set_pin_l0_filter_and_index_blocks_in_cache(
+        block_options: *mut DBBlockBasedTableOptions, v: c_uchar);
+    pub fn rocksdb_block_based_options_set_pin_top_level_index_and_filter(
+        block_options: *mut DBBlockBasedTableOptions, v: c_uchar);
     pub fn rocksdb_block_based_options_set_filter_policy(
         block_options: *mut DBBlockBasedTableOptions,
         filter_policy: *mut DBFilterPolicy);
@@ -128,9 +312,6 @@ extern "C" {
         block_options: *mut DBBlockBasedTableOptions, no_block_cache: bool);
     pub fn rocksdb_block_based_options_set_block_cache(
         block_options: *mut DBBlockBasedTableOptions, block_cache: *mut DBCache);
-    pub fn rocksdb_block_based_options_set_block_cache_compressed(
-        block_options: *mut DBBlockBasedTableOptions,
-        block_cache_compressed: *mut DBCache);
     pub fn rocksdb_block_based_options_set_whole_key_filtering(
         ck_options: *mut DBBlockBasedTableOptions, doit: bool);
     pub fn rocksdb_options_set_block_based_table_factory(
@@ -139,14 +320,27 @@ extern "C" {
     pub fn rocksdb_options_increase_parallelism(options: *mut DBOptions, threads: c_int);
     pub fn rocksdb_options_optimize_level_style_compaction(options: *mut DBOptions,
                                                            memtable_memory_budget: c_int);
+    pub fn rocksdb_options_prepare_for_bulk_load(options: *mut DBOptions);
     pub fn rocksdb_options_set_compaction_filter(options: *mut DBOptions,
                                                  filter: *mut DBCompactionFilter);
     pub fn rocksdb_options_set_create_if_missing(options: *mut DBOptions, v: bool);
     pub fn rocksdb_options_set_max_open_files(options: *mut DBOptions, files: c_int);
     pub fn rocksdb_options_set_use_fsync(options: *mut DBOptions, v: c_int);
     pub fn rocksdb_options_set_bytes_per_sync(options: *mut DBOptions, bytes: u64);
-    pub fn rocksdb_options_set_disable_data_sync(options: *mut DBOptions, v: c_int);
-    pub fn rocksdb_options_set_allow_os_buffer(options: *mut DBOptions, is_allow: bool);
+    pub fn rocksdb_options_get_bytes_per_sync(options: *mut DBOptions) -> u64;
+    pub fn rocksdb_options_set_wal_bytes_per_sync(options: *mut DBOptions, bytes: u64);
+    pub fn rocksdb_options_set_manual_wal_flush(options: *mut DBOptions, v: bool);
+    pub fn rocksdb_options_get_wal_bytes_per_sync(options: *mut DBOptions) -> u64;
+    pub fn rocksdb_options_set_max_write_batch_group_size_bytes(options: *mut DBOptions,
+                                                                bytes: u64);
+    pub fn rocksdb_options_set_allow_mmap_reads(options: *mut DBOptions, v: bool);
+    pub fn rocksdb_options_set_allow_mmap_writes(options: *mut DBOptions, v: bool);
+    pub fn rocksdb_options_set_use_direct_reads(options: *mut DBOptions, v: bool);
+    pub fn rocksdb_options_set_use_direct_io_for_flush_and_compaction(options: *mut DBOptions,
+                                                                      v: bool);
+    pub fn rocksdb_options_set_advise_random_on_open(options: *mut DBOptions, v: bool);
+    pub fn rocksdb_options_set_is_fd_close_on_exec(options: *mut DBOptions, v: bool);
+    pub fn rocksdb_options_set_max_file_opening_threads(options: *mut DBOptions, n: c_int);
     pub fn rocksdb_options_optimize_for_point_lookup(options: *mut DBOptions,
                                                      block_cache_size_mb: u64);
     pub fn rocksdb_options_set_table_cache_numshardbits(options: *mut DBOptions, bits: c_int);
@@ -162,7 +356,7 @@ extern "C" {
     pub fn rocksdb_options_set_target_file_size_multiplier(options: *mut DBOptions, mul: c_int);
     pub fn rocksdb_options_set_max_bytes_for_level_base(options: *mut DBOptions, bytes: u64);
     pub fn rocksdb_options_set_max_bytes_for_level_multiplier(options: *mut DBOptions,
-                                                              mul: c_int);
+                                                              mul: c_double);
     pub fn rocksdb_options_set_max_log_file_size(options: *mut DBOptions, bytes: u64);
     pub fn rocksdb_options_set_max_manifest_file_size(options: *mut DBOptions, bytes: u64);
     pub fn rocksdb_options_set_hash_skip_list_rep(options: *mut DBOptions,
@@ -179,22 +373,99 @@ extern "C" {
                                                           max_bg_compactions: c_int);
     pub fn rocksdb_options_set_max_background_flushes(options: *mut DBOptions,
                                                       max_bg_flushes: c_int);
-    pub fn rocksdb_options_set_filter_deletes(options: *mut DBOptions, v: bool);
     pub fn rocksdb_options_set_disable_auto_compactions(options: *mut DBOptions, v: c_int);
     pub fn rocksdb_options_set_report_bg_io_stats(options: *mut DBOptions, v: c_int);
+    pub fn rocksdb_options_set_avoid_unnecessary_blocking_io(options: *mut DBOptions, v: bool);
+    pub fn rocksdb_options_set_allow_ingest_behind(options: *mut DBOptions, v: bool);
+    pub fn rocksdb_options_set_skip_checking_sst_file_sizes_on_db_open(options: *mut DBOptions,
+                                                                       v: bool);
+    pub fn rocksdb_options_set_paranoid_checks(options: *mut DBOptions, v: bool);
+    pub fn rocksdb_options_set_skip_stats_update_on_db_open(options: *mut DBOptions, v: bool);
     pub fn rocksdb_options_set_wal_recovery_mode(options: *mut DBOptions, mode: DBRecoveryMode);
+    pub fn rocksdb_options_set_track_and_verify_wals_in_manifest(options: *mut DBOptions,
+                                                                 v: bool);
+    pub fn rocksdb_options_get_track_and_verify_wals_in_manifest(options: *mut DBOptions)
+                                                                 -> c_uchar;
     pub fn rocksdb_options_enable_statistics(options: *mut DBOptions);
     pub fn rocksdb_options_statistics_get_string(options: *mut DBOptions) -> *const c_char;
+    pub fn rocksdb_options_statistics_get_histogram_data(options: *mut DBOptions,
+                                                         hist_type: DBStatisticsHistogramType,
+                                                         data: *mut DBHistogramData);
+    pub fn rocksdb_statistics_histogram_data_create() -> *mut DBHistogramData;
+    pub fn rocksdb_statistics_histogram_data_destroy(data: *mut DBHistogramData);
+    pub fn rocksdb_statistics_histogram_data_get_median(data: *mut DBHistogramData) -> c_double;
+    pub fn rocksdb_statistics_histogram_data_get_p95(data: *mut DBHistogramData) -> c_double;
+    pub fn rocksdb_statistics_histogram_data_get_p99(data: *mut DBHistogramData) -> c_double;
+    pub fn rocksdb_statistics_histogram_data_get_average(data: *mut DBHistogramData) -> c_double;
+    pub fn rocksdb_statistics_histogram_data_get_std_dev(data: *mut DBHistogramData) -> c_double;
+    pub fn rocksdb_statistics_histogram_data_get_max(data: *mut DBHistogramData) -> c_double;
+    pub fn rocksdb_statistics_histogram_data_get_count(data: *mut DBHistogramData) -> u64;
+    pub fn rocksdb_statistics_histogram_data_get_sum(data: *mut DBHistogramData) -> u64;
+    pub fn rocksdb_statistics_histogram_data_get_min(data: *mut DBHistogramData) -> c_double;
     pub fn rocksdb_options_set_stats_dump_period_sec(options: *mut DBOptions, v: usize);
+    pub fn rocksdb_options_set_stats_persist_period_sec(options: *mut DBOptions, v: c_uint);
+    pub fn rocksdb_options_get_stats_persist_period_sec(options: *mut DBOptions) -> c_uint;
     pub fn rocksdb_options_set_num_levels(options: *mut DBOptions, v: c_int);
     pub fn rocksdb_options_set_ratelimiter(options: *mut DBOptions, limiter: *mut DBRateLimiter);
     pub fn rocksdb_ratelimiter_create(rate_bytes_per_sec: i64,
                                       refill_period_us: i64,
                                       fairness: i32)
                                       -> *mut DBRateLimiter;
+    pub fn rocksdb_ratelimiter_create_auto_tuned(rate_bytes_per_sec: i64,
+                                                 refill_period_us: i64,
+                                                 fairness: i32)
+                                                 -> *mut DBRateLimiter;
+    pub fn rocksdb_ratelimiter_create_with_mode(rate_bytes_per_sec: i64,
+                                                refill_period_us: i64,
+                                                fairness: i32,
+                                                mode: DBRateLimiterMode,
+                                                auto_tuned: bool)
+                                                -> *mut DBRateLimiter;
     pub fn rocksdb_ratelimiter_destroy(limiter: *mut DBRateLimiter);
-    pub fn rocksdb_filterpolicy_create_bloom_full(bits_per_key: c_int) -> *mut DBFilterPolicy;
-    pub fn rocksdb_filterpolicy_create_bloom(bits_per_key: c_int) -> *mut DBFilterPolicy;
+    pub fn rocksdb_options_set_wal_dir(options: *mut DBOptions, path: *const c_char);
+    pub fn rocksdb_dbpath_create(path: *const c_char, target_size: u64) -> *mut DBPath;
+    pub fn rocksdb_dbpath_destroy(dbpath: *mut DBPath);
+    pub fn rocksdb_options_set_db_paths(options: *mut DBOptions,
+                                        path_values: *const *const DBPath,
+                                        num_paths: size_t);
+    pub fn rocksdb_options_set_info_log_level(options: *mut DBOptions, level: DBInfoLogLevel);
+    pub fn rocksdb_options_set_info_log(options: *mut DBOptions, logger: *mut DBLogger);
+    pub fn rocksdb_logger_create_callback_logger(level: c_int,
+                                                 logv: extern "C" fn(*mut c_void,
+                                                                     c_uint,
+                                                                     *mut c_char,
+                                                                     size_t),
+                                                 state: *mut c_void)
+                                                 -> *mut DBLogger;
+    pub fn rocksdb_logger_destroy(logger: *mut DBLogger);
+    pub fn rocksdb_options_set_env(options: *mut DBOptions, env: *mut DBEnv);
+    pub fn rocksdb_create_default_env() -> *mut DBEnv;
+    pub fn rocksdb_load_latest_options(db_path: *const c_char,
+                                       env: *mut DBEnv,
+                                       ignore_unknown_options: bool,
+                                       cache: *mut DBCache,
+                                       db_options: *mut *mut DBOptions,
+                                       num_column_families: *mut size_t,
+                                       column_family_names: *mut *mut *mut c_char,
+                                       column_family_options: *mut *mut *mut DBOptions,
+                                       err: *mut *mut c_char);
+    pub fn rocksdb_env_destroy(env: *mut DBEnv);
+    pub fn rocksdb_env_set_background_threads(env: *mut DBEnv, n: c_int);
+    pub fn rocksdb_env_get_background_threads(env: *mut DBEnv) -> c_int;
+    pub fn rocksdb_env_set_high_priority_background_threads(env: *mut DBEnv, n: c_int);
+    pub fn rocksdb_env_get_high_priority_background_threads(env: *mut DBEnv) -> c_int;
+    pub fn rocksdb_env_lower_thread_pool_io_priority(env: *mut DBEnv);
+    pub fn rocksdb_env_lower_high_priority_thread_pool_io_priority(env: *mut DBEnv);
+    pub fn rocksdb_env_lower_thread_pool_cpu_priority(env: *mut DBEnv);
+    pub fn rocksdb_env_lower_high_priority_thread_pool_cpu_priority(env: *mut DBEnv);
+    pub fn rocksdb_filterpolicy_create_bloom_full(bits_per_key: c_double)
+                                                  -> *mut DBFilterPolicy;
+    pub fn rocksdb_filterpolicy_create_bloom(bits_per_key: c_double) -> *mut DBFilterPolicy;
+    pub fn rocksdb_filterpolicy_create_ribbon(bloom_equivalent_bits_per_key: c_double)
+                                              -> *mut DBFilterPolicy;
+    pub fn rocksdb_filterpolicy_create_ribbon_hybrid(bloom_equivalent_bits_per_key: c_double,
+                                                     bloom_before_level: c_int)
+                                                     -> *mut DBFilterPolicy;
     pub fn rocksdb_open(options: *mut DBOptions,
                         path: *const c_char,
                         err: *mut *mut c_char)
@@ -218,6 +489,16 @@ extern "C" {
                           v: *const u8,
                           vLen: size_t,
                           err: *mut *mut c_char);
+    pub fn rocksdb_put_cf_with_ts(db: *mut DBInstance,
+                                  writeopts: *mut DBWriteOptions,
+                                  cf: *mut DBCFHandle,
+                                  k: *const u8,
+                                  kLen: size_t,
+                                  ts: *const u8,
+                                  tsLen: size_t,
+                                  v: *const u8,
+                                  vLen: size_t,
+                                  err: *mut *mut c_char);
     pub fn rocksdb_readoptions_create() -> *mut DBReadOptions;
     pub fn rocksdb_readoptions_destroy(readopts: *mut DBReadOptions);
     pub fn rocksdb_readoptions_set_verify_checksums(readopts: *mut DBReadOptions, v: bool);
@@ -229,6 +510,20 @@ extern "C" {
                                                        kLen: size_t);
     pub fn rocksdb_readoptions_set_read_tier(readopts: *mut DBReadOptions, tier: c_int);
     pub fn rocksdb_readoptions_set_tailing(readopts: *mut DBReadOptions, v: bool);
+    pub fn rocksdb_readoptions_set_readahead_size(readopts: *mut DBReadOptions, size: size_t);
+    pub fn rocksdb_readoptions_set_ignore_range_deletions(readopts: *mut DBReadOptions, v: bool);
+    pub fn rocksdb_readoptions_set_pin_data(readopts: *mut DBReadOptions, v: bool);
+    pub fn rocksdb_readoptions_set_auto_prefix_mode(readopts: *mut DBReadOptions, v: bool);
+    pub fn rocksdb_readoptions_set_deadline(readopts: *mut DBReadOptions, microseconds: u64);
+    pub fn rocksdb_readoptions_set_io_timeout(readopts: *mut DBReadOptions, microseconds: u64);
+    pub fn rocksdb_readoptions_set_background_purge_on_iterator_cleanup(readopts: *mut DBReadOptions,
+                                                                        v: bool);
+    pub fn rocksdb_readoptions_set_timestamp(readopts: *mut DBReadOptions,
+                                             ts: *const u8,
+                                             tsLen: size_t);
+    pub fn rocksdb_readoptions_set_iter_start_ts(readopts: *mut DBReadOptions,
+                                                 ts: *const u8,
+                                                 tsLen: size_t);
 
     pub fn rocksdb_get(db: *const DBInstance,
                        readopts: *const DBReadOptions,
@@ -245,6 +540,21 @@ extern "C" {
                           valLen: *const size_t,
                           err: *mut *mut c_char)
                           -> *mut u8;
+    pub fn rocksdb_get_cf_with_ts(db: *const DBInstance,
+                                  readopts: *const DBReadOptions,
+                                  cf_handle: *mut DBCFHandle,
+                                  k: *const u8,
+                                  kLen: size_t,
+                                  valLen: *mut size_t,
+                                  ts: *mut *mut u8,
+                                  tsLen: *mut size_t,
+                                  err: *mut *mut c_char)
+                                  -> *mut u8;
+    pub fn rocksdb_increase_full_history_ts_low(db: *mut DBInstance,
+                                                cf_handle: *mut DBCFHandle,
+                                                ts: *const u8,
+                                                tsLen: size_t,
+                                                err: *mut *mut c_char);
     pub fn rocksdb_create_iterator(db: *mut DBInstance,
                                    readopts: *const DBReadOptions)
                                    -> *mut DBIterator;
@@ -252,8 +562,15 @@ extern "C" {
                                       readopts: *const DBReadOptions,
                                       cf_handle: *mut DBCFHandle)
                                       -> *mut DBIterator;
+    pub fn rocksdb_create_iterators(db: *mut DBInstance,
+                                    readopts: *mut DBReadOptions,
+                                    cf_handles: *const *mut DBCFHandle,
+                                    iterators: *mut *mut DBIterator,
+                                    size: size_t,
+                                    err: *mut *mut c_char);
     pub fn rocksdb_create_snapshot(db: *mut DBInstance) -> *const DBSnapshot;
     pub fn rocksdb_release_snapshot(db: *mut DBInstance, snapshot: *const DBSnapshot);
+    pub fn rocksdb_snapshot_get_sequence_number(snapshot: *const DBSnapshot) -> u64;
 
     pub fn rocksdb_delete(db: *mut DBInstance,
                           writeopts: *const DBWriteOptions,
@@ -266,6 +583,14 @@ extern "C" {
                              k: *const u8,
                              kLen: size_t,
                              err: *mut *mut c_char);
+    pub fn rocksdb_delete_range_cf(db: *mut DBInstance,
+                                   writeopts: *const DBWriteOptions,
+                                   cf: *mut DBCFHandle,
+                                   begin_key: *const u8,
+                                   begin_key_len: size_t,
+                                   end_key: *const u8,
+                                   end_key_len: size_t,
+                                   err: *mut *mut c_char);
     pub fn rocksdb_close(db: *mut DBInstance);
     pub fn rocksdb_destroy_db(options: *const DBOptions,
                               path: *const c_char,
@@ -326,7 +651,9 @@ extern "C" {
     pub fn rocksdb_iter_prev(iter: *mut DBIterator);
     pub fn rocksdb_iter_key(iter: *const DBIterator, klen: *mut size_t) -> *mut u8;
     pub fn rocksdb_iter_value(iter: *const DBIterator, vlen: *mut size_t) -> *mut u8;
+    pub fn rocksdb_iter_timestamp(iter: *const DBIterator, tslen: *mut size_t) -> *const u8;
     pub fn rocksdb_iter_get_error(iter: *const DBIterator, err: *mut *mut c_char);
+    pub fn rocksdb_iter_refresh(iter: *mut DBIterator, err: *mut *mut c_char);
     // Write batch
     pub fn rocksdb_write(db: *mut DBInstance,
                          writeopts: *const DBWriteOptions,
@@ -348,6 +675,14 @@ extern "C" {
                                      klen: size_t,
                                      val: *const u8,
                                      vlen: size_t);
+    pub fn rocksdb_writebatch_put_cf_with_ts(batch: *mut DBWriteBatch,
+                                             cf: *mut DBCFHandle,
+                                             key: *const u8,
+                                             klen: size_t,
+                                             ts: *const u8,
+                                             tslen: size_t,
+                                             val: *const u8,
+                                             vlen: size_t);
     pub fn rocksdb_writebatch_merge(batch: *mut DBWriteBatch,
                                     key: *const u8,
                                     klen: size_t,
@@ -364,6 +699,24 @@ extern "C" {
                                         cf: *mut DBCFHandle,
                                         key: *const u8,
                                         klen: size_t);
+    pub fn rocksdb_writebatch_delete_range(batch: *mut DBWriteBatch,
+                                           begin_key: *const u8,
+                                           begin_key_len: size_t,
+                                           end_key: *const u8,
+                                           end_key_len: size_t);
+    pub fn rocksdb_writebatch_delete_range_cf(batch: *mut DBWriteBatch,
+                                              cf: *mut DBCFHandle,
+                                              begin_key: *const u8,
+                                              begin_key_len: size_t,
+                                              end_key: *const u8,
+                                              end_key_len: size_t);
+    pub fn rocksdb_writebatch_put_log_data(batch: *mut DBWriteBatch,
+                                           blob: *const u8,
+                                           len: size_t);
+    pub fn rocksdb_writebatch_set_save_point(batch: *mut DBWriteBatch);
+    pub fn rocksdb_writebatch_rollback_to_save_point(batch: *mut DBWriteBatch,
+                                                     err: *mut *mut c_char);
+    pub fn rocksdb_writebatch_pop_save_point(batch: *mut DBWriteBatch, err: *mut *mut c_char);
     pub fn rocksdb_writebatch_iterate(batch: *mut DBWriteBatch,
                                       state: *mut c_void,
                                       put_fn: extern "C" fn(state: *mut c_void,
@@ -388,6 +741,31 @@ extern "C" {
                                                             -> c_int,
                                      name_fn: extern "C" fn(*mut c_void) -> *const c_char)
                                      -> *mut DBComparator;
+    pub fn rocksdb_comparator_with_ts_create(state: *mut c_void,
+                                             destroy: extern "C" fn(*mut c_void) -> (),
+                                             compare: extern "C" fn(arg: *mut c_void,
+                                                                    a: *const c_char,
+                                                                    alen: size_t,
+                                                                    b: *const c_char,
+                                                                    blen: size_t)
+                                                                    -> c_int,
+                                             compare_ts: extern "C" fn(arg: *mut c_void,
+                                                                       a_ts: *const c_char,
+                                                                       a_tslen: size_t,
+                                                                       b_ts: *const c_char,
+                                                                       b_tslen: size_t)
+                                                                       -> c_int,
+                                             compare_without_ts: extern "C" fn(arg: *mut c_void,
+                                                                               a: *const c_char,
+                                                                               alen: size_t,
+                                                                               a_has_ts: bool,
+                                                                               b: *const c_char,
+                                                                               blen: size_t,
+                                                                               b_has_ts: bool)
+                                                                               -> c_int,
+                                             name_fn: extern "C" fn(*mut c_void) -> *const c_char,
+                                             timestamp_size: size_t)
+                                             -> *mut DBComparator;
     pub fn rocksdb_comparator_destroy(cmp: *mut DBComparator);
 
     // Column Family
@@ -420,9 +798,66 @@ extern "C" {
     pub fn rocksdb_flushoptions_destroy(opt: *mut DBFlushOptions);
     pub fn rocksdb_flushoptions_set_wait(opt: *mut DBFlushOptions, whether_wait: bool);
 
+    // EnvOptions
+    pub fn rocksdb_envoptions_create() -> *mut DBEnvOptions;
+    pub fn rocksdb_envoptions_destroy(opt: *mut DBEnvOptions);
+
+    // IngestExternalFileOptions
+    pub fn rocksdb_ingestexternalfileoptions_create() -> *mut DBIngestExternalFileOptions;
+    pub fn rocksdb_ingestexternalfileoptions_set_move_files(opt: *mut DBIngestExternalFileOptions,
+                                                            move_files: bool);
+    pub fn rocksdb_ingestexternalfileoptions_set_snapshot_consistency(
+        opt: *mut DBIngestExternalFileOptions, snapshot_consistency: bool);
+    pub fn rocksdb_ingestexternalfileoptions_set_allow_global_seqno(
+        opt: *mut DBIngestExternalFileOptions, allow_global_seqno: bool);
+    pub fn rocksdb_ingestexternalfileoptions_set_allow_blocking_flush(
+        opt: *mut DBIngestExternalFileOptions, allow_blocking_flush: bool);
+    pub fn rocksdb_ingestexternalfileoptions_set_ingest_behind(
+        opt: *mut DBIngestExternalFileOptions, ingest_behind: bool);
+    pub fn rocksdb_ingestexternalfileoptions_destroy(opt: *mut DBIngestExternalFileOptions);
+
+    // SstFileWriter
+    pub fn rocksdb_sstfilewriter_create(env: *mut DBEnvOptions,
+                                        io_options: *const DBOptions)
+                                        -> *mut DBSstFileWriter;
+    pub fn rocksdb_sstfilewriter_open(writer: *mut DBSstFileWriter,
+                                      name: *const c_char,
+                                      err: *mut *mut c_char);
+    pub fn rocksdb_sstfilewriter_put(writer: *mut DBSstFileWriter,
+                                     key: *const u8,
+                                     key_len: size_t,
+                                     val: *const u8,
+                                     val_len: size_t,
+                                     err: *mut *mut c_char);
+    pub fn rocksdb_sstfilewriter_merge(writer: *mut DBSstFileWriter,
+                                       key: *const u8,
+                                       key_len: size_t,
+                                       val: *const u8,
+                                       val_len: size_t,
+                                       err: *mut *mut c_char);
+    pub fn rocksdb_sstfilewriter_delete(writer: *mut DBSstFileWriter,
+                                        key: *const u8,
+                                        key_len: size_t,
+                                        err: *mut *mut c_char);
+    pub fn rocksdb_sstfilewriter_finish(writer: *mut DBSstFileWriter, err: *mut *mut c_char);
+    pub fn rocksdb_sstfilewriter_file_size(writer: *mut DBSstFileWriter, file_size: *mut u64);
+    pub fn rocksdb_sstfilewriter_destroy(writer: *mut DBSstFileWriter);
+    pub fn rocksdb_ingest_external_file(db: *mut DBInstance,
+                                        file_list: *const *const c_char,
+                                        list_len: size_t,
+                                        opt: *const DBIngestExternalFileOptions,
+                                        err: *mut *mut c_char);
+    pub fn rocksdb_ingest_external_file_cf(db: *mut DBInstance,
+                                           handle: *mut DBCFHandle,
+                                           file_list: *const *const c_char,
+                                           list_len: size_t,
+                                           opt: *const DBIngestExternalFileOptions,
+                                           err: *mut *mut c_char);
+
     pub fn rocksdb_flush(db: *mut DBInstance,
                          options: *const DBFlushOptions,
                          err: *mut *mut c_char);
+    pub fn rocksdb_flush_wal(db: *mut DBInstance, sync: bool, err: *mut *mut c_char);
 
     pub fn rocksdb_approximate_sizes(db: *mut DBInstance,
                                      num_ranges: c_int,
@@ -430,7 +865,8 @@ extern "C" {
                                      range_start_key_len: *const size_t,
                                      range_limit_key: *const *const u8,
                                      range_limit_key_len: *const size_t,
-                                     sizes: *mut uint64_t);
+                                     sizes: *mut uint64_t,
+                                     err: *mut *mut c_char);
     pub fn rocksdb_approximate_sizes_cf(db: *mut DBInstance,
                                         cf: *mut DBCFHandle,
                                         num_ranges: c_int,
@@ -438,7 +874,16 @@ extern "C" {
                                         range_start_key_len: *const size_t,
                                         range_limit_key: *const *const u8,
                                         range_limit_key_len: *const size_t,
-                                        sizes: *mut uint64_t);
+                                        sizes: *mut uint64_t,
+                                        err: *mut *mut c_char);
+    pub fn rocksdb_approximate_memtable_stats_cf(db: *const DBInstance,
+                                                 cf: *const DBCFHandle,
+                                                 start_key: *const u8,
+                                                 start_key_len: size_t,
+                                                 limit_key: *const u8,
+                                                 limit_key_len: size_t,
+                                                 count: *mut u64,
+                                                 size: *mut u64);
     pub fn rocksdb_compact_range(db: *mut DBInstance,
                                  start_key: *const u8,
                                  start_key_len: size_t,
@@ -450,6 +895,22 @@ extern "C" {
                                     start_key_len: size_t,
                                     limit_key: *const u8,
                                     limit_key_len: size_t);
+    pub fn rocksdb_cancel_all_background_work(db: *mut DBInstance, wait: bool);
+    pub fn rocksdb_disable_manual_compaction(db: *mut DBInstance);
+    pub fn rocksdb_enable_manual_compaction(db: *mut DBInstance);
+    pub fn rocksdb_memory_consumers_create() -> *mut DBMemoryConsumers;
+    pub fn rocksdb_memory_consumers_add_db(consumers: *mut DBMemoryConsumers, db: *mut DBInstance);
+    pub fn rocksdb_memory_consumers_destroy(consumers: *mut DBMemoryConsumers);
+    pub fn rocksdb_approximate_memory_usage_create(consumers: *mut DBMemoryConsumers,
+                                                   err: *mut *mut c_char)
+                                                   -> *mut DBMemoryUsage;
+    pub fn rocksdb_approximate_memory_usage_destroy(usage: *mut DBMemoryUsage);
+    pub fn rocksdb_approximate_memory_usage_get_mem_table_total(usage: *mut DBMemoryUsage) -> u64;
+    pub fn rocksdb_approximate_memory_usage_get_mem_table_unflushed(usage: *mut DBMemoryUsage)
+                                                                    -> u64;
+    pub fn rocksdb_approximate_memory_usage_get_mem_table_readers_total(usage: *mut DBMemoryUsage)
+                                                                        -> u64;
+    pub fn rocksdb_approximate_memory_usage_get_cache_total(usage: *mut DBMemoryUsage) -> u64;
     pub fn rocksdb_delete_file_in_range(db: *mut DBInstance,
                                         range_start_key: *const u8,
                                         range_start_key_len: size_t,
@@ -545,7 +1006,9 @@ mod test {
                                       vec![1].as_ptr(),
                                       vec![b"\xff\x00".as_ptr()].as_ptr(),
                                       vec![1].as_ptr(),
-                                      sizes.as_mut_ptr());
+                                      sizes.as_mut_ptr(),
+                                      &mut err);
+            assert!(err.is_null(), error_message(err));
             assert_eq!(sizes.len(), 1);
             assert!(sizes[0] > 0);
 
@@ -14,6 +14,7 @@
 //
 
 use libc::{c_char, c_int, c_void, size_t};
+use std::cmp::Ordering;
 use std::ffi::CString;
 use std::mem;
 use std::slice;
@@ -49,3 +50,103 @@ pub extern "C" fn compare_callback(raw_cb: *mut c_void,
         (cb.f)(a, b)
     }
 }
+
+/// The size of the timestamps used by the `u64` timestamp comparator.
+pub const U64_TS_SIZE: usize = 8;
+
+pub static U64_TS_COMPARATOR_NAME: &[u8] = b"leveldb.BytewiseComparator.u64ts\0";
+
+pub fn encode_u64_ts(ts: u64) -> [u8; U64_TS_SIZE] {
+    ts.to_le_bytes()
+}
+
+pub fn decode_u64_ts(ts: &[u8]) -> u64 {
+    let mut buf = [0; U64_TS_SIZE];
+    buf.copy_from_slice(ts);
+    u64::from_le_bytes(buf)
+}
+
+pub extern "C" fn u64_ts_destructor_callback(_: *mut c_void) {}
+
+pub extern "C" fn u64_ts_name_callback(_: *mut c_void) -> *const c_char {
+    U64_TS_COMPARATOR_NAME.as_ptr() as *const c_char
+}
+
+// The callbacks below run inside RocksDB, so a panic would abort the
+// process. RocksDB only passes them keys and timestamps of
+// `U64_TS_SIZE`, anything shorter is compared as a key without a
+// timestamp, or a timestamp of 0, instead of panicking.
+fn split_u64_ts(key: &[u8]) -> (&[u8], u64) {
+    if key.len() < U64_TS_SIZE {
+        return (key, 0);
+    }
+    let (key, ts) = key.split_at(key.len() - U64_TS_SIZE);
+    (key, decode_u64_ts(ts))
+}
+
+fn strip_u64_ts(key: &[u8], has_ts: bool) -> &[u8] {
+    if has_ts && key.len() >= U64_TS_SIZE {
+        &key[..key.len() - U64_TS_SIZE]
+    } else {
+        key
+    }
+}
+
+// Keys are suffixed with their timestamps, the user keys are compared
+// bytewise and then the larger timestamp goes first, so the newest version
+// is seen first.
+pub extern "C" fn u64_ts_compare_callback(_: *mut c_void,
+                                          a_raw: *const c_char,
+                                          a_len: size_t,
+                                          b_raw: *const c_char,
+                                          b_len: size_t)
+                                          -> c_int {
+    unsafe {
+        let a: &[u8] = slice::from_raw_parts(a_raw as *const u8, a_len);
+        let b: &[u8] = slice::from_raw_parts(b_raw as *const u8, b_len);
+        let (a_key, a_ts) = split_u64_ts(a);
+        let (b_key, b_ts) = split_u64_ts(b);
+        match a_key.cmp(b_key) {
+            Ordering::Equal => ordering_to_int(b_ts.cmp(&a_ts)),
+            o => ordering_to_int(o),
+        }
+    }
+}
+
+pub extern "C" fn u64_ts_compare_ts_callback(_: *mut c_void,
+                                             a_raw: *const c_char,
+                                             a_len: size_t,
+                                             b_raw: *const c_char,
+                                             b_len: size_t)
+                                             -> c_int {
+    unsafe {
+        let a: &[u8] = slice::from_raw_parts(a_raw as *const u8, a_len);
+        let b: &[u8] = slice::from_raw_parts(b_raw as *const u8, b_len);
+        let a_ts = if a.len() == U64_TS_SIZE { decode_u64_ts(a) } else { 0 };
+        let b_ts = if b.len() == U64_TS_SIZE { decode_u64_ts(b) } else { 0 };
+        ordering_to_int(a_ts.cmp(&b_ts))
+    }
+}
+
+pub extern "C" fn u64_ts_compare_without_ts_callback(_: *mut c_void,
+                                                     a_raw: *const c_char,
+                                                     a_len: size_t,
+                                                     a_has_ts: bool,
+                                                     b_raw: *const c_char,
+                                                     b_len: size_t,
+                                                     b_has_ts: bool)
+                                                     -> c_int {
+    unsafe {
+        let a: &[u8] = slice::from_raw_parts(a_raw as *const u8, a_len);
+        let b: &[u8] = slice::from_raw_parts(b_raw as *const u8, b_len);
+        ordering_to_int(strip_u64_ts(a, a_has_ts).cmp(strip_u64_ts(b, b_has_ts)))
+    }
+}
+
+fn ordering_to_int(o: Ordering) -> c_int {
+    match o {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    }
+}
@@ -25,10 +25,21 @@ pub mod rocksdb_options;
 pub mod merge_operator;
 pub mod comparator;
 mod compaction_filter;
+mod logger;
 
 pub use compaction_filter::CompactionFilter;
-pub use librocksdb_sys::{DBCompactionStyle, DBCompressionType, DBRecoveryMode, new_bloom_filter,
+pub use logger::Logger;
+pub use librocksdb_sys::{DBCompactionStyle, DBCompressionType, DBRecoveryMode, DBChecksumType,
+                         DBDataBlockIndexType, DBInfoLogLevel, DBPrepopulateBlockCache,
+                         DBRateLimiterMode,
+                         DBStatisticsHistogramType, DBStatisticsTickerType,
+                         new_bloom_filter,
                          self as rocksdb_ffi};
 pub use merge_operator::MergeOperands;
-pub use rocksdb::{DB, DBIterator, DBVector, Kv, SeekKey, Writable, WriteBatch, CFHandle, Range};
-pub use rocksdb_options::{BlockBasedOptions, Options, ReadOptions, WriteOptions};
+pub use rocksdb::{DB, DBIterator, DBVector, Kv, SeekKey, Writable, WriteBatch, WriteBatchIter,
+                  WriteBatchRecord, CFHandle, CfMemoryUsage, MemoryUsageType, MultiCfIterator,
+                  OwnedCFHandle, Range, SstFileWriter, DB_LOCKED_ERROR_PREFIX,
+                  get_approximate_memory_usage_by_type};
+pub use rocksdb_options::{BlockBasedOptions, Env, EnvOptions, HistogramData,
+                          IngestExternalFileOptions, Options, RateLimiter, ReadOptions,
+                          WriteOptions, load_latest_options};
@@ -0,0 +1,62 @@
+use libc::{c_void, c_char, c_int, c_uint, size_t};
+
+use rocksdb_ffi::{self, DBInfoLogLevel, DBLogger};
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+
+/// `Logger` receives the info log lines generated by rocksdb.
+///
+/// It may be called from rocksdb's background threads. A panic in `logv`
+/// is caught and the line is dropped, as it can't unwind into rocksdb.
+pub trait Logger: Send + Sync {
+    fn logv(&self, level: DBInfoLogLevel, msg: &str);
+}
+
+struct LoggerProxy {
+    logger: Box<dyn Logger>,
+}
+
+extern "C" fn logv(logger: *mut c_void, level: c_uint, msg: *mut c_char, len: size_t) {
+    unsafe {
+        let proxy = &*(logger as *mut LoggerProxy);
+        let level = match level {
+            0 => DBInfoLogLevel::Debug,
+            1 => DBInfoLogLevel::Info,
+            2 => DBInfoLogLevel::Warn,
+            3 => DBInfoLogLevel::Error,
+            4 => DBInfoLogLevel::Fatal,
+            _ => DBInfoLogLevel::Header,
+        };
+        let msg = slice::from_raw_parts(msg as *const u8, len);
+        let msg = String::from_utf8_lossy(msg);
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| proxy.logger.logv(level, &msg)));
+    }
+}
+
+/// Keeps the rust logger alive as long as rocksdb may call it.
+pub struct LoggerHandle {
+    pub inner: *mut DBLogger,
+    _proxy: Box<LoggerProxy>,
+}
+
+impl Drop for LoggerHandle {
+    fn drop(&mut self) {
+        unsafe {
+            rocksdb_ffi::rocksdb_logger_destroy(self.inner);
+        }
+    }
+}
+
+pub fn new_logger(level: DBInfoLogLevel, l: Box<dyn Logger>) -> LoggerHandle {
+    let mut proxy = Box::new(LoggerProxy { logger: l });
+    let inner = unsafe {
+        rocksdb_ffi::rocksdb_logger_create_callback_logger(level as c_int,
+                                                           logv,
+                                                           &mut *proxy as *mut LoggerProxy as
+                                                           *mut c_void)
+    };
+    LoggerHandle {
+        inner,
+        _proxy: proxy,
+    }
+}
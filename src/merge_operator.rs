@@ -147,7 +147,7 @@ impl<'a> Iterator for &'a mut MergeOperands {
 
 #[cfg(test)]
 mod test {
-    use rocksdb::{DB, DBVector, Writable};
+    use rocksdb::{DB, DBVector, Writable, WriteBatch};
     use rocksdb_options::Options;
     use super::*;
     use tempdir::TempDir;
@@ -206,4 +206,32 @@ mod test {
         assert!(db.delete(b"k1").is_ok());
         assert!(db.get(b"k1").unwrap().is_none());
     }
+
+    #[test]
+    fn writebatch_merge_test() {
+        let path = TempDir::new("_rust_rocksdb_writebatch_merge").expect("");
+        let mut opts = Options::new();
+        opts.create_if_missing(true);
+        opts.add_merge_operator("test operator", test_provided_merge);
+        let mut db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+        let mut cf_opts = Options::new();
+        cf_opts.add_merge_operator("test operator", test_provided_merge);
+        db.create_cf("cf1", &cf_opts).unwrap();
+        let cf1 = db.cf_handle("cf1").unwrap();
+        db.put(b"k1", b"a").unwrap();
+
+        let batch = WriteBatch::new();
+        batch.merge(b"k1", b"b").unwrap();
+        batch.put(b"k2", b"c").unwrap();
+        batch.put_cf(cf1, b"k3", b"d").unwrap();
+        batch.merge_cf(cf1, b"k3", b"e").unwrap();
+        assert_eq!(batch.count(), 4);
+        assert!(db.get(b"k2").unwrap().is_none());
+        assert_eq!(&*db.get(b"k1").unwrap().unwrap(), b"a");
+
+        db.write(batch).unwrap();
+        assert_eq!(&*db.get(b"k1").unwrap().unwrap(), b"ab");
+        assert_eq!(&*db.get(b"k2").unwrap().unwrap(), b"c");
+        assert_eq!(&*db.get_cf(cf1, b"k3").unwrap().unwrap(), b"de");
+    }
 }
@@ -14,37 +14,160 @@
 //
 
 
+use comparator;
 use libc::{self, c_int, c_void, size_t};
 
-use rocksdb_ffi::{self, DBWriteBatch, DBCFHandle, DBInstance};
-use rocksdb_options::{Options, ReadOptions, UnsafeSnap, WriteOptions, FlushOptions};
-use std::collections::BTreeMap;
-use std::collections::btree_map::Entry;
+use rocksdb_ffi::{self, DBWriteBatch, DBCFHandle, DBInstance, DBSstFileWriter,
+                  DBStatisticsHistogramType, DBStatisticsTickerType};
+use rocksdb_options::{Options, ReadOptions, UnsafeSnap, WriteOptions, FlushOptions, EnvOptions,
+                      IngestExternalFileOptions, HistogramData};
+use std::ascii;
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap};
 use std::ffi::{CStr, CString};
+use std::fmt;
 use std::fs;
 use std::ops::Deref;
 use std::path::Path;
 use std::ptr;
 use std::slice;
 use std::str::from_utf8;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
 const DEFAULT_COLUMN_FAMILY: &'static str = "default";
 
+// The sequence number and the count at the beginning of a serialized
+// write batch.
+const WRITE_BATCH_HEADER_SIZE: usize = 12;
+
+/// The prefix of the error returned by `DB::open` and `DB::open_cf` when
+/// the db directory is already opened by another DB instance.
+pub const DB_LOCKED_ERROR_PREFIX: &str = "DB is locked by another instance: ";
+
+// The errors of RocksDB 9.10.0 when it fails to lock the LOCK file, which
+// has no status code of its own. It's held by another process if it's
+//   IO error: While lock file: <path>/LOCK: <strerror>
+// and by another DB instance in this process if it's
+//   IO error: lock hold by current process, acquire time <secs> acquiring
+//   thread <id>: <path>/LOCK: No locks available
+fn is_db_locked_error(e: &str) -> bool {
+    (e.starts_with("IO error: While lock file: ") ||
+     e.starts_with("IO error: lock hold by current process, acquire time ")) &&
+    e.contains("/LOCK: ")
+}
+
 pub struct CFHandle {
     inner: *mut DBCFHandle,
+    // Shared with the owning DB, set to true once the DB is closed.
+    db_closed: Arc<Mutex<bool>>,
 }
 
+unsafe impl Send for CFHandle {}
+unsafe impl Sync for CFHandle {}
+
 impl Drop for CFHandle {
     fn drop(&mut self) {
-        unsafe {
-            rocksdb_ffi::rocksdb_column_family_handle_destroy(self.inner);
+        let closed = self.db_closed.lock().unwrap();
+        // Destroying a handle after its DB is closed would touch the closed
+        // DB, so the handle is leaked on purpose instead.
+        if !*closed {
+            unsafe {
+                rocksdb_ffi::rocksdb_column_family_handle_destroy(self.inner);
+            }
+        }
+    }
+}
+
+/// A column family handle that is not bound to the lifetime of the DB.
+///
+/// If the column family is dropped while an `OwnedCFHandle` is alive, reads
+/// and writes through it keep working on the dropped column family, and its
+/// data is only cleaned up after the last handle is released.
+///
+/// The handle must be released before the DB is closed. If the last
+/// `OwnedCFHandle` outlives the DB, the underlying handle is leaked, as it
+/// can't be destroyed once the DB is closed.
+#[derive(Clone)]
+pub struct OwnedCFHandle {
+    handle: Arc<CFHandle>,
+}
+
+impl Deref for OwnedCFHandle {
+    type Target = CFHandle;
+
+    fn deref(&self) -> &CFHandle {
+        &self.handle
+    }
+}
+
+/// Memory usage of a column family, in bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CfMemoryUsage {
+    /// Size of the active, unflushed immutable and pinned immutable
+    /// memtables.
+    pub mem_table_total: u64,
+    /// Size of the active and unflushed immutable memtables.
+    pub mem_table_unflushed: u64,
+    /// Memory used for reading sst files, excluding the block cache.
+    pub mem_table_readers_total: u64,
+    /// Usage of the block cache, it may be shared with other column
+    /// families.
+    pub cache_total: u64,
+}
+
+/// Kinds of memory reported by `get_approximate_memory_usage_by_type`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MemoryUsageType {
+    /// Size of all the memtables.
+    MemTableTotal,
+    /// Size of the memtables that are not yet flushed.
+    MemTableUnflushed,
+    /// Memory used for reading sst files, excluding the block cache.
+    MemTableReadersTotal,
+    /// Usage of the block caches, a cache shared by several DBs is only
+    /// counted once.
+    CacheTotal,
+}
+
+/// Return the approximate memory usage of `dbs` by type.
+///
+/// The block caches are collected from the options of the column families
+/// of `dbs`.
+pub fn get_approximate_memory_usage_by_type(dbs: &[&DB])
+                                            -> Result<HashMap<MemoryUsageType, u64>, String> {
+    unsafe {
+        let consumers = rocksdb_ffi::rocksdb_memory_consumers_create();
+        for db in dbs {
+            rocksdb_ffi::rocksdb_memory_consumers_add_db(consumers, db.inner);
         }
+        let mut err = ptr::null_mut();
+        let usage = rocksdb_ffi::rocksdb_approximate_memory_usage_create(consumers, &mut err);
+        rocksdb_ffi::rocksdb_memory_consumers_destroy(consumers);
+        if !err.is_null() {
+            return Err(rocksdb_ffi::error_message(err));
+        }
+
+        let mut res = HashMap::new();
+        res.insert(MemoryUsageType::MemTableTotal,
+                   rocksdb_ffi::rocksdb_approximate_memory_usage_get_mem_table_total(usage));
+        res.insert(MemoryUsageType::MemTableUnflushed,
+                   rocksdb_ffi::rocksdb_approximate_memory_usage_get_mem_table_unflushed(usage));
+        let readers_total =
+            rocksdb_ffi::rocksdb_approximate_memory_usage_get_mem_table_readers_total(usage);
+        res.insert(MemoryUsageType::MemTableReadersTotal, readers_total);
+        res.insert(MemoryUsageType::CacheTotal,
+                   rocksdb_ffi::rocksdb_approximate_memory_usage_get_cache_total(usage));
+        rocksdb_ffi::rocksdb_approximate_memory_usage_destroy(usage);
+        Ok(res)
     }
 }
 
 pub struct DB {
     inner: *mut DBInstance,
-    cfs: BTreeMap<String, CFHandle>,
+    cfs: BTreeMap<String, Arc<CFHandle>>,
+    closed: Arc<Mutex<bool>>,
     path: String,
     opts: Options,
 }
@@ -54,6 +177,8 @@ unsafe impl Sync for DB {}
 
 pub struct WriteBatch {
     inner: *mut DBWriteBatch,
+    // 0 means unlimited.
+    max_bytes: usize,
 }
 
 pub struct Snapshot<'a> {
@@ -121,6 +246,13 @@ impl<'a> DBIterator<'a> {
         self.valid()
     }
 
+    /// Return the key at the current position without copying.
+    ///
+    /// The slice points into the iterator's internal buffer, so it's only
+    /// valid until the iterator is moved again. Moving the iterator takes
+    /// `&mut self`, hence the compiler rejects any use after that.
+    ///
+    /// Panics if the iterator is not valid.
     pub fn key(&self) -> &[u8] {
         assert!(self.valid());
         let mut key_len: size_t = 0;
@@ -131,6 +263,11 @@ impl<'a> DBIterator<'a> {
         }
     }
 
+    /// Return the value at the current position without copying.
+    ///
+    /// Same as `key`, the slice is only valid until the iterator is moved.
+    ///
+    /// Panics if the iterator is not valid.
     pub fn value(&self) -> &[u8] {
         assert!(self.valid());
         let mut val_len: size_t = 0;
@@ -141,6 +278,23 @@ impl<'a> DBIterator<'a> {
         }
     }
 
+    /// Return the timestamp of the entry at the current position, `None`
+    /// if the column family doesn't use the `u64` timestamp comparator.
+    ///
+    /// Panics if the iterator is not valid.
+    pub fn timestamp(&self) -> Option<u64> {
+        assert!(self.valid());
+        let mut ts_len: size_t = 0;
+        unsafe {
+            let ts_ptr = rocksdb_ffi::rocksdb_iter_timestamp(self.inner, &mut ts_len);
+            if ts_len == 0 {
+                return None;
+            }
+            Some(comparator::decode_u64_ts(slice::from_raw_parts(ts_ptr, ts_len)))
+        }
+    }
+
+    /// Return a copy of the key-value pair at the current position.
     pub fn kv(&self) -> Option<(Vec<u8>, Vec<u8>)> {
         if self.valid() {
             Some((self.key().to_vec(), self.value().to_vec()))
@@ -153,6 +307,36 @@ impl<'a> DBIterator<'a> {
         unsafe { rocksdb_ffi::rocksdb_iter_valid(self.inner) }
     }
 
+    /// Return the error that occurred during iteration, if any.
+    ///
+    /// An iterator also becomes invalid when it hits an error, so check
+    /// status after `valid()` returns false to tell an error from the end.
+    pub fn status(&self) -> Result<(), String> {
+        unsafe {
+            ffi_try!(rocksdb_iter_get_error(self.inner));
+        }
+        Ok(())
+    }
+
+    /// Update the iterator to read the latest data of the db, so the
+    /// writes made after it was created become visible.
+    ///
+    /// The iterator has to be positioned again with `seek` afterwards.
+    pub fn refresh(&mut self) -> Result<(), String> {
+        unsafe {
+            ffi_try!(rocksdb_iter_refresh(self.inner));
+        }
+        Ok(())
+    }
+
+    /// Return an iterator which walks backward from the current position.
+    ///
+    /// Seek to `SeekKey::End` first to get all the key-value pairs in
+    /// descending order.
+    pub fn reverse<'b>(&'b mut self) -> ReverseIter<'b, 'a> {
+        ReverseIter { iter: self }
+    }
+
     pub fn new_cf(db: &'a DB, cf_handle: &CFHandle, readopts: ReadOptions) -> DBIterator<'a> {
         unsafe {
             let iterator = rocksdb_ffi::rocksdb_create_iterator_cf(db.inner,
@@ -181,6 +365,22 @@ impl<'b, 'a> Iterator for &'b mut DBIterator<'a> {
     }
 }
 
+pub struct ReverseIter<'b, 'a: 'b> {
+    iter: &'b mut DBIterator<'a>,
+}
+
+impl<'b, 'a> Iterator for ReverseIter<'b, 'a> {
+    type Item = Kv;
+
+    fn next(&mut self) -> Option<Kv> {
+        let kv = self.iter.kv();
+        if kv.is_some() {
+            self.iter.prev();
+        }
+        kv
+    }
+}
+
 impl<'a> Drop for DBIterator<'a> {
     fn drop(&mut self) {
         unsafe {
@@ -189,6 +389,175 @@ impl<'a> Drop for DBIterator<'a> {
     }
 }
 
+/// An iterator that merges several column families into one scan.
+///
+/// Keys are yielded in bytewise order across all the column families,
+/// along with the index of the column family in the slice passed to
+/// `DB::iter_cfs`. If a key exists in several column families, it's
+/// yielded once for each of them, in the order of their indexes.
+///
+/// It only iterates forward, there's no `prev`.
+pub struct MultiCfIterator<'a> {
+    _db: &'a DB,
+    // All the iterators share the same read options and view of the db,
+    // the options must outlive them as they point to its upper bound.
+    _readopts: &'a ReadOptions,
+    iters: Vec<*mut rocksdb_ffi::DBIterator>,
+    // Index of the iterator at the smallest key.
+    cur: Option<usize>,
+}
+
+impl<'a> MultiCfIterator<'a> {
+    fn new(db: &'a DB,
+           readopts: &'a ReadOptions,
+           cfs: &[&CFHandle])
+           -> Result<MultiCfIterator<'a>, String> {
+        let handles: Vec<_> = cfs.iter().map(|cf| cf.inner).collect();
+        let mut iters = vec![ptr::null_mut(); cfs.len()];
+        unsafe {
+            ffi_try!(rocksdb_create_iterators(db.inner,
+                                              readopts.get_inner() as *mut _,
+                                              handles.as_ptr(),
+                                              iters.as_mut_ptr(),
+                                              iters.len() as size_t));
+        }
+        Ok(MultiCfIterator {
+            _db: db,
+            _readopts: readopts,
+            iters,
+            cur: None,
+        })
+    }
+
+    /// Seek all the column families to `key`.
+    ///
+    /// `SeekKey::End` positions the iterator after the last key, which
+    /// leaves it invalid as it can't move backward.
+    pub fn seek(&mut self, key: SeekKey) -> bool {
+        for &iter in &self.iters {
+            unsafe {
+                match key {
+                    SeekKey::Start => rocksdb_ffi::rocksdb_iter_seek_to_first(iter),
+                    SeekKey::End => {
+                        rocksdb_ffi::rocksdb_iter_seek_to_last(iter);
+                        if rocksdb_ffi::rocksdb_iter_valid(iter) {
+                            rocksdb_ffi::rocksdb_iter_next(iter);
+                        }
+                    }
+                    SeekKey::Key(key) => {
+                        rocksdb_ffi::rocksdb_iter_seek(iter, key.as_ptr(), key.len() as size_t)
+                    }
+                }
+            }
+        }
+        self.update_cur();
+        self.valid()
+    }
+
+    /// Move to the next key, return whether the iterator is still valid.
+    pub fn advance(&mut self) -> bool {
+        if let Some(i) = self.cur {
+            unsafe {
+                rocksdb_ffi::rocksdb_iter_next(self.iters[i]);
+            }
+            self.update_cur();
+        }
+        self.valid()
+    }
+
+    pub fn valid(&self) -> bool {
+        self.cur.is_some()
+    }
+
+    /// Return the index of the column family the current key is from.
+    ///
+    /// Panics if the iterator is not valid.
+    pub fn cf_index(&self) -> usize {
+        self.cur.unwrap()
+    }
+
+    /// Return the key at the current position without copying.
+    ///
+    /// Panics if the iterator is not valid.
+    pub fn key(&self) -> &[u8] {
+        self.key_at(self.cf_index())
+    }
+
+    /// Return the value at the current position without copying.
+    ///
+    /// Panics if the iterator is not valid.
+    pub fn value(&self) -> &[u8] {
+        let mut val_len: size_t = 0;
+        unsafe {
+            let val_ptr = rocksdb_ffi::rocksdb_iter_value(self.iters[self.cf_index()],
+                                                          &mut val_len);
+            slice::from_raw_parts(val_ptr, val_len as usize)
+        }
+    }
+
+    /// Return the first error that occurred in any column family, if any.
+    pub fn status(&self) -> Result<(), String> {
+        for &iter in &self.iters {
+            unsafe {
+                ffi_try!(rocksdb_iter_get_error(iter));
+            }
+        }
+        Ok(())
+    }
+
+    // The key of the iterator of column family `i`, which must be valid.
+    fn key_at(&self, i: usize) -> &[u8] {
+        let mut key_len: size_t = 0;
+        unsafe {
+            let key_ptr = rocksdb_ffi::rocksdb_iter_key(self.iters[i], &mut key_len);
+            slice::from_raw_parts(key_ptr, key_len)
+        }
+    }
+
+    fn update_cur(&mut self) {
+        let mut cur: Option<(usize, &[u8])> = None;
+        for (i, &iter) in self.iters.iter().enumerate() {
+            if unsafe { !rocksdb_ffi::rocksdb_iter_valid(iter) } {
+                continue;
+            }
+            let key = self.key_at(i);
+            // Ties are broken by the index, so only a strictly smaller
+            // key replaces the current one.
+            let smaller = match cur {
+                Some((_, k)) => key < k,
+                None => true,
+            };
+            if smaller {
+                cur = Some((i, key));
+            }
+        }
+        self.cur = cur.map(|(i, _)| i);
+    }
+}
+
+impl<'b, 'a> Iterator for &'b mut MultiCfIterator<'a> {
+    type Item = (usize, Vec<u8>, Vec<u8>);
+
+    fn next(&mut self) -> Option<(usize, Vec<u8>, Vec<u8>)> {
+        if !self.valid() {
+            return None;
+        }
+        let item = (self.cf_index(), self.key().to_vec(), self.value().to_vec());
+        self.advance();
+        Some(item)
+    }
+}
+
+impl<'a> Drop for MultiCfIterator<'a> {
+    fn drop(&mut self) {
+        for &iter in &self.iters {
+            unsafe {
+                rocksdb_ffi::rocksdb_iter_destroy(iter);
+            }
+        }
+    }
+}
+
 impl<'a> Snapshot<'a> {
     pub fn new(db: &DB) -> Snapshot {
         unsafe {
@@ -226,6 +595,19 @@ impl<'a> Snapshot<'a> {
         }
         self.db.get_cf_opt(cf, key, &readopts)
     }
+
+    /// Return the sequence number of the latest write visible to the
+    /// snapshot.
+    ///
+    /// RocksDB only reads at a sequence number held by a live snapshot,
+    /// the older versions may be compacted away once it's released, so
+    /// there's no way to read at a recorded sequence number later. Use
+    /// `Options::set_comparator_with_u64_ts` and
+    /// `ReadOptions::set_timestamp` for that instead, reads below the
+    /// `DB::increase_full_history_ts_low` of the column family fail.
+    pub fn get_sequence_number(&self) -> u64 {
+        unsafe { self.snap.get_sequence_number() }
+    }
 }
 
 impl<'a> Drop for Snapshot<'a> {
@@ -242,6 +624,13 @@ pub trait Writable {
     fn merge_cf(&self, cf: &CFHandle, key: &[u8], value: &[u8]) -> Result<(), String>;
     fn delete(&self, key: &[u8]) -> Result<(), String>;
     fn delete_cf(&self, cf: &CFHandle, key: &[u8]) -> Result<(), String>;
+    /// Remove all the keys in `[begin_key, end_key)`.
+    fn delete_range(&self, begin_key: &[u8], end_key: &[u8]) -> Result<(), String>;
+    fn delete_range_cf(&self,
+                       cf: &CFHandle,
+                       begin_key: &[u8],
+                       end_key: &[u8])
+                       -> Result<(), String>;
 }
 
 /// A range of keys, `start_key` is included, but not `end_key`.
@@ -253,8 +642,16 @@ pub struct Range<'a> {
 }
 
 impl<'a> Range<'a> {
+    /// Create a range borrowing `start_key` and `end_key`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start_key` is greater than `end_key` in bytewise order.
     pub fn new(start_key: &'a [u8], end_key: &'a [u8]) -> Range<'a> {
-        assert!(start_key <= end_key);
+        assert!(start_key <= end_key,
+                "start_key {:?} is greater than end_key {:?}",
+                start_key,
+                end_key);
         Range {
             start_key: start_key,
             end_key: end_key,
@@ -295,6 +692,7 @@ impl DB {
             return Err(format!("cfs.len() and cf_opts.len() not match."));
         }
 
+        let closed = Arc::new(Mutex::new(false));
         let (db, cf_map) = {
             let mut cfs_v = cfs.to_vec();
             let mut cf_opts_v = cf_opts.to_vec();
@@ -323,14 +721,23 @@ impl DB {
                 .map(|x| x.inner as *const rocksdb_ffi::DBOptions)
                 .collect();
 
+            let mut err = ptr::null_mut();
             let db = unsafe {
-                ffi_try!(rocksdb_open_column_families(opts.inner,
-                                                      cpath.as_ptr(),
-                                                      cfs_v.len() as c_int,
-                                                      cfnames.as_ptr(),
-                                                      cfopts.as_ptr(),
-                                                      cfhandles.as_ptr()))
+                rocksdb_ffi::rocksdb_open_column_families(opts.inner,
+                                                          cpath.as_ptr(),
+                                                          cfs_v.len() as c_int,
+                                                          cfnames.as_ptr(),
+                                                          cfopts.as_ptr(),
+                                                          cfhandles.as_ptr(),
+                                                          &mut err)
             };
+            if !err.is_null() {
+                let e = rocksdb_ffi::error_message(err);
+                if is_db_locked_error(&e) {
+                    return Err(format!("{}{}", DB_LOCKED_ERROR_PREFIX, e));
+                }
+                return Err(e);
+            }
 
             for handle in &cfhandles {
                 if handle.is_null() {
@@ -340,7 +747,11 @@ impl DB {
 
             let mut cf_map = BTreeMap::new();
             for (n, h) in cfs_v.iter().zip(cfhandles) {
-                cf_map.insert((*n).to_owned(), CFHandle { inner: h });
+                let handle = CFHandle {
+                    inner: h,
+                    db_closed: closed.clone(),
+                };
+                cf_map.insert((*n).to_owned(), Arc::new(handle));
             }
 
             if db.is_null() {
@@ -353,11 +764,15 @@ impl DB {
         Ok(DB {
             inner: db,
             cfs: cf_map,
+            closed,
             path: path.to_owned(),
             opts: opts,
         })
     }
 
+    /// Remove all the db files in `path`.
+    ///
+    /// Fails if the db is still opened, since the lock is held.
     pub fn destroy(opts: &Options, path: &str) -> Result<(), String> {
         let cpath = CString::new(path.as_bytes()).unwrap();
         unsafe {
@@ -366,7 +781,12 @@ impl DB {
         Ok(())
     }
 
-    pub fn repair(opts: Options, path: &str) -> Result<(), String> {
+    /// Try to rebuild the MANIFEST from the sst files and WALs found in
+    /// `path`, so that a db with a corrupted or missing MANIFEST can be
+    /// opened again.
+    ///
+    /// Some data may be lost, and the db must not be opened while repairing.
+    pub fn repair(opts: &Options, path: &str) -> Result<(), String> {
         let cpath = CString::new(path.as_bytes()).unwrap();
         unsafe {
             ffi_try!(rocksdb_repair_db(opts.inner, cpath.as_ptr()));
@@ -403,6 +823,7 @@ impl DB {
         Ok(cfs)
     }
 
+    /// Return the path the db is opened with.
     pub fn path(&self) -> &str {
         &self.path
     }
@@ -471,7 +892,67 @@ impl DB {
         self.get_cf_opt(cf, key, &ReadOptions::new())
     }
 
+    /// Return the newest version of `key` visible at the timestamp set on
+    /// `readopts`, along with the timestamp it was written at.
+    ///
+    /// `cf` must use `Options::set_comparator_with_u64_ts`, and `readopts`
+    /// must have a timestamp set by `ReadOptions::set_timestamp`.
+    pub fn get_cf_with_ts(&self,
+                          cf: &CFHandle,
+                          key: &[u8],
+                          readopts: &ReadOptions)
+                          -> Result<Option<(DBVector, u64)>, String> {
+        unsafe {
+            let mut val_len: size_t = 0;
+            let mut ts: *mut u8 = ptr::null_mut();
+            let mut ts_len: size_t = 0;
+            let val = ffi_try!(rocksdb_get_cf_with_ts(self.inner,
+                                                      readopts.get_inner(),
+                                                      cf.inner,
+                                                      key.as_ptr(),
+                                                      key.len() as size_t,
+                                                      &mut val_len,
+                                                      &mut ts,
+                                                      &mut ts_len));
+            let decoded_ts = if ts.is_null() {
+                0
+            } else {
+                let decoded = comparator::decode_u64_ts(slice::from_raw_parts(ts, ts_len));
+                libc::free(ts as *mut c_void);
+                decoded
+            };
+            if val.is_null() {
+                Ok(None)
+            } else {
+                Ok(Some((DBVector::from_c(val, val_len), decoded_ts)))
+            }
+        }
+    }
+
+    /// Allow the versions of `cf` older than `ts` to be garbage collected
+    /// by compactions, reads below `ts` fail afterwards.
+    ///
+    /// `ts` can only increase, setting a lower one returns an error.
+    pub fn increase_full_history_ts_low(&self, cf: &CFHandle, ts: u64) -> Result<(), String> {
+        let ts = comparator::encode_u64_ts(ts);
+        unsafe {
+            ffi_try!(rocksdb_increase_full_history_ts_low(self.inner,
+                                                          cf.inner,
+                                                          ts.as_ptr(),
+                                                          ts.len() as size_t));
+            Ok(())
+        }
+    }
+
+    /// Create a column family `name` with `opts` and return its handle.
+    ///
+    /// The column family options, such as merge operator, comparator and
+    /// block based options, are taken from `opts`. Creating a column family
+    /// that already exists is an error.
     pub fn create_cf(&mut self, name: &str, opts: &Options) -> Result<&CFHandle, String> {
+        if self.cfs.contains_key(name) {
+            return Err(format!("Column family {} already exists", name));
+        }
         let cname = match CString::new(name.as_bytes()) {
             Ok(c) => c,
             Err(_) => {
@@ -482,17 +963,19 @@ impl DB {
         unsafe {
             let cf_handler =
                 ffi_try!(rocksdb_create_column_family(self.inner, opts.inner, cname_ptr));
-            let handle = CFHandle { inner: cf_handler };
-            Ok(match self.cfs.entry(name.to_owned()) {
-                Entry::Occupied(mut e) => {
-                    e.insert(handle);
-                    e.into_mut()
-                }
-                Entry::Vacant(e) => e.insert(handle),
-            })
+            let handle = Arc::new(CFHandle {
+                inner: cf_handler,
+                db_closed: self.closed.clone(),
+            });
+            Ok(self.cfs.entry(name.to_owned()).or_insert(handle))
         }
     }
 
+    /// Drop the column family `name`.
+    ///
+    /// Handles returned by `cf_handle_owned` stay usable after the column
+    /// family is dropped, the column family is only released after all of
+    /// them are gone.
     pub fn drop_cf(&mut self, name: &str) -> Result<(), String> {
         let cf = self.cfs.remove(name);
         if cf.is_none() {
@@ -506,8 +989,17 @@ impl DB {
         Ok(())
     }
 
+    /// Get the handle of column family `name`, "default" is always
+    /// available.
     pub fn cf_handle(&self, name: &str) -> Option<&CFHandle> {
-        self.cfs.get(name)
+        self.cfs.get(name).map(|h| &**h)
+    }
+
+    /// Get a handle of column family `name` that may outlive `drop_cf`.
+    ///
+    /// See also `OwnedCFHandle`.
+    pub fn cf_handle_owned(&self, name: &str) -> Option<OwnedCFHandle> {
+        self.cfs.get(name).map(|h| OwnedCFHandle { handle: h.clone() })
     }
 
     /// get all column family names, including 'default'.
@@ -529,6 +1021,17 @@ impl DB {
         DBIterator::new_cf(self, cf_handle, opts)
     }
 
+    /// Iterate over the column families in `cfs` as if they were merged
+    /// into one, all of them use `opt`.
+    ///
+    /// See also `MultiCfIterator`.
+    pub fn iter_cfs<'a>(&'a self,
+                        opt: &'a ReadOptions,
+                        cfs: &[&CFHandle])
+                        -> Result<MultiCfIterator<'a>, String> {
+        MultiCfIterator::new(self, opt, cfs)
+    }
+
     pub fn snapshot(&self) -> Snapshot {
         Snapshot::new(self)
     }
@@ -541,6 +1044,11 @@ impl DB {
         rocksdb_ffi::rocksdb_release_snapshot(self.inner, snap.get_inner())
     }
 
+    /// Write a single key with `writeopts`.
+    ///
+    /// Like the other single key write methods, it calls rocksdb directly
+    /// without building a `WriteBatch`, so `writeopts` can be reused across
+    /// calls on the hot path.
     pub fn put_opt(&self,
                    key: &[u8],
                    value: &[u8],
@@ -574,6 +1082,31 @@ impl DB {
             Ok(())
         }
     }
+
+    /// Write version `ts` of `key` into `cf`, which must use
+    /// `Options::set_comparator_with_u64_ts`.
+    pub fn put_cf_with_ts(&self,
+                          cf: &CFHandle,
+                          key: &[u8],
+                          ts: u64,
+                          value: &[u8])
+                          -> Result<(), String> {
+        let ts = comparator::encode_u64_ts(ts);
+        let writeopts = WriteOptions::new();
+        unsafe {
+            ffi_try!(rocksdb_put_cf_with_ts(self.inner,
+                                            writeopts.inner,
+                                            cf.inner,
+                                            key.as_ptr(),
+                                            key.len() as size_t,
+                                            ts.as_ptr(),
+                                            ts.len() as size_t,
+                                            value.as_ptr(),
+                                            value.len() as size_t));
+            Ok(())
+        }
+    }
+
     pub fn merge_opt(&self,
                      key: &[u8],
                      value: &[u8],
@@ -589,12 +1122,13 @@ impl DB {
             Ok(())
         }
     }
-    fn merge_cf_opt(&self,
-                    cf: &CFHandle,
-                    key: &[u8],
-                    value: &[u8],
-                    writeopts: &WriteOptions)
-                    -> Result<(), String> {
+
+    pub fn merge_cf_opt(&self,
+                        cf: &CFHandle,
+                        key: &[u8],
+                        value: &[u8],
+                        writeopts: &WriteOptions)
+                        -> Result<(), String> {
         unsafe {
             ffi_try!(rocksdb_merge_cf(self.inner,
                                       writeopts.inner,
@@ -606,7 +1140,8 @@ impl DB {
             Ok(())
         }
     }
-    fn delete_opt(&self, key: &[u8], writeopts: &WriteOptions) -> Result<(), String> {
+
+    pub fn delete_opt(&self, key: &[u8], writeopts: &WriteOptions) -> Result<(), String> {
         unsafe {
             ffi_try!(rocksdb_delete(self.inner,
                                     writeopts.inner,
@@ -616,11 +1151,11 @@ impl DB {
         }
     }
 
-    fn delete_cf_opt(&self,
-                     cf: &CFHandle,
-                     key: &[u8],
-                     writeopts: &WriteOptions)
-                     -> Result<(), String> {
+    pub fn delete_cf_opt(&self,
+                         cf: &CFHandle,
+                         key: &[u8],
+                         writeopts: &WriteOptions)
+                         -> Result<(), String> {
         unsafe {
             ffi_try!(rocksdb_delete_cf(self.inner,
                                        writeopts.inner,
@@ -631,6 +1166,33 @@ impl DB {
         }
     }
 
+    pub fn delete_range_opt(&self,
+                            begin_key: &[u8],
+                            end_key: &[u8],
+                            writeopts: &WriteOptions)
+                            -> Result<(), String> {
+        let cf = self.cfs.get(DEFAULT_COLUMN_FAMILY).unwrap();
+        self.delete_range_cf_opt(cf, begin_key, end_key, writeopts)
+    }
+
+    pub fn delete_range_cf_opt(&self,
+                               cf: &CFHandle,
+                               begin_key: &[u8],
+                               end_key: &[u8],
+                               writeopts: &WriteOptions)
+                               -> Result<(), String> {
+        unsafe {
+            ffi_try!(rocksdb_delete_range_cf(self.inner,
+                                             writeopts.inner,
+                                             cf.inner,
+                                             begin_key.as_ptr(),
+                                             begin_key.len() as size_t,
+                                             end_key.as_ptr(),
+                                             end_key.len() as size_t));
+            Ok(())
+        }
+    }
+
     /// Flush all memtable data.
     ///
     /// Due to lack of abi, only default cf is supported.
@@ -645,6 +1207,76 @@ impl DB {
         }
     }
 
+    /// Write the buffered WAL data to the WAL file, and sync it if `sync`.
+    ///
+    /// It's only needed when the db is opened with
+    /// `Options::set_manual_wal_flush`.
+    pub fn flush_wal(&self, sync: bool) -> Result<(), String> {
+        unsafe {
+            ffi_try!(rocksdb_flush_wal(self.inner, sync));
+        }
+        Ok(())
+    }
+
+    /// Load the sst files in `files` into the db.
+    pub fn ingest_external_file(&self,
+                                opt: &IngestExternalFileOptions,
+                                files: &[&str])
+                                -> Result<(), String> {
+        let cf = self.cfs.get(DEFAULT_COLUMN_FAMILY).unwrap();
+        self.ingest_external_file_cf(cf, opt, files)
+    }
+
+    /// Load the sst files in `files` into column family `cf`.
+    pub fn ingest_external_file_cf(&self,
+                                   cf: &CFHandle,
+                                   opt: &IngestExternalFileOptions,
+                                   files: &[&str])
+                                   -> Result<(), String> {
+        let c_files: Vec<CString> = files.iter()
+            .map(|f| CString::new(f.as_bytes()).unwrap())
+            .collect();
+        let c_files_ptrs: Vec<*const _> = c_files.iter().map(|f| f.as_ptr()).collect();
+        unsafe {
+            ffi_try!(rocksdb_ingest_external_file_cf(self.inner,
+                                                     cf.inner,
+                                                     c_files_ptrs.as_ptr(),
+                                                     c_files_ptrs.len() as size_t,
+                                                     opt.inner));
+            Ok(())
+        }
+    }
+
+    /// Stop all the background flushes and compactions, and wait for the
+    /// running ones to finish if `wait` is true.
+    ///
+    /// This is meant to be called before shutdown, background work is not
+    /// scheduled again afterwards, but reads and writes still succeed.
+    pub fn cancel_all_background_work(&self, wait: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_cancel_all_background_work(self.inner, wait);
+        }
+    }
+
+    /// Abort the running manual compactions and make the new ones return
+    /// early with "Manual compaction paused" until
+    /// `enable_manual_compaction` is called.
+    ///
+    /// The calls nest, manual compactions are only resumed after
+    /// `enable_manual_compaction` is called once for each call of it.
+    pub fn disable_manual_compaction(&self) {
+        unsafe {
+            rocksdb_ffi::rocksdb_disable_manual_compaction(self.inner);
+        }
+    }
+
+    /// Resume the manual compactions paused by `disable_manual_compaction`.
+    pub fn enable_manual_compaction(&self) {
+        unsafe {
+            rocksdb_ffi::rocksdb_enable_manual_compaction(self.inner);
+        }
+    }
+
     /// Return the approximate file system space used by keys in each ranges.
     ///
     /// Note that the returned sizes measure file system space usage, so
@@ -652,15 +1284,21 @@ impl DB {
     /// sizes will be one-tenth the size of the corresponding user data size.
     ///
     /// Due to lack of abi, only data flushed to disk is taken into account.
-    pub fn get_approximate_sizes(&self, ranges: &[Range]) -> Vec<u64> {
+    pub fn get_approximate_sizes(&self, ranges: &[Range]) -> Result<Vec<u64>, String> {
         self.get_approximate_sizes_cfopt(None, ranges)
     }
 
-    pub fn get_approximate_sizes_cf(&self, cf: &CFHandle, ranges: &[Range]) -> Vec<u64> {
+    pub fn get_approximate_sizes_cf(&self,
+                                    cf: &CFHandle,
+                                    ranges: &[Range])
+                                    -> Result<Vec<u64>, String> {
         self.get_approximate_sizes_cfopt(Some(cf), ranges)
     }
 
-    fn get_approximate_sizes_cfopt(&self, cf: Option<&CFHandle>, ranges: &[Range]) -> Vec<u64> {
+    fn get_approximate_sizes_cfopt(&self,
+                                   cf: Option<&CFHandle>,
+                                   ranges: &[Range])
+                                   -> Result<Vec<u64>, String> {
         let start_keys: Vec<*const u8> = ranges.iter()
             .map(|x| x.start_key.as_ptr())
             .collect();
@@ -681,6 +1319,7 @@ impl DB {
              end_keys.as_ptr(),
              end_key_lens.as_ptr(),
              sizes.as_mut_ptr());
+        let mut err = ptr::null_mut();
         match cf {
             None => unsafe {
                 rocksdb_ffi::rocksdb_approximate_sizes(self.inner,
@@ -689,7 +1328,8 @@ impl DB {
                                                        start_key_len_ptr,
                                                        end_key_ptr,
                                                        end_key_len_ptr,
-                                                       size_ptr)
+                                                       size_ptr,
+                                                       &mut err)
             },
             Some(cf) => unsafe {
                 rocksdb_ffi::rocksdb_approximate_sizes_cf(self.inner,
@@ -699,10 +1339,14 @@ impl DB {
                                                           start_key_len_ptr,
                                                           end_key_ptr,
                                                           end_key_len_ptr,
-                                                          size_ptr)
+                                                          size_ptr,
+                                                          &mut err)
             },
         }
-        sizes
+        if !err.is_null() {
+            return Err(rocksdb_ffi::error_message(err));
+        }
+        Ok(sizes)
     }
 
     pub fn compact_range(&self, start_key: Option<&[u8]>, end_key: Option<&[u8]>) {
@@ -724,6 +1368,48 @@ impl DB {
         }
     }
 
+    /// Like `compact_range`, but also wait for the automatic compactions
+    /// it triggers.
+    ///
+    /// After the manual compaction finishes, it waits until no compaction
+    /// is pending or running, and returns an error if that doesn't happen
+    /// within `timeout`. The manual compaction itself is not limited by
+    /// `timeout`.
+    pub fn compact_range_and_wait(&self,
+                                  start_key: Option<&[u8]>,
+                                  end_key: Option<&[u8]>,
+                                  timeout: Duration)
+                                  -> Result<(), String> {
+        self.compact_range(start_key, end_key);
+        self.wait_for_compactions(None, timeout)
+    }
+
+    pub fn compact_range_cf_and_wait(&self,
+                                     cf: &CFHandle,
+                                     start_key: Option<&[u8]>,
+                                     end_key: Option<&[u8]>,
+                                     timeout: Duration)
+                                     -> Result<(), String> {
+        self.compact_range_cf(cf, start_key, end_key);
+        self.wait_for_compactions(Some(cf), timeout)
+    }
+
+    fn wait_for_compactions(&self, cf: Option<&CFHandle>, timeout: Duration) -> Result<(), String> {
+        let start = Instant::now();
+        loop {
+            let pending = self.get_property_int_cf_opt(cf, "rocksdb.compaction-pending");
+            // The number of running compactions is only available db wide.
+            let running = self.get_property_int("rocksdb.num-running-compactions");
+            if pending.unwrap_or(0) == 0 && running.unwrap_or(0) == 0 {
+                return Ok(());
+            }
+            if start.elapsed() >= timeout {
+                return Err(format!("compactions are still running after {:?}", timeout));
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
     pub fn delete_file_in_range(&self, start_key: &[u8], end_key: &[u8]) -> Result<(), String> {
         unsafe {
             ffi_try!(rocksdb_delete_file_in_range(self.inner,
@@ -769,6 +1455,119 @@ impl DB {
         self.get_property_int_cf_opt(Some(cf), name)
     }
 
+    /// Return the sum of the int property over all the column families.
+    /// Return None if the property not exists or not int type in any of
+    /// them.
+    ///
+    /// The C API has no `GetAggregatedIntProperty`, so the sum is taken
+    /// here and only makes sense for per column family properties like
+    /// `rocksdb.cur-size-all-mem-tables`. A db-wide property like
+    /// `rocksdb.block-cache-usage` or `rocksdb.num-snapshots` has the same
+    /// value in every column family and is counted once for each of them,
+    /// use `get_property_int` for those.
+    pub fn get_aggregated_int_property(&self, name: &str) -> Option<u64> {
+        let mut sum = 0;
+        for cf in self.cfs.values() {
+            sum += self.get_property_int_cf(cf, name)?;
+        }
+        Some(sum)
+    }
+
+    /// Return the memory used by the block cache, which is shared by all
+    /// column families using it.
+    pub fn get_block_cache_usage(&self) -> Option<u64> {
+        self.get_property_int("rocksdb.block-cache-usage")
+    }
+
+    /// Return the approximate size of the active and unflushed immutable
+    /// memtables of `cf`.
+    pub fn get_memtable_size_cf(&self, cf: &CFHandle) -> Option<u64> {
+        self.get_property_int_cf(cf, "rocksdb.cur-size-all-mem-tables")
+    }
+
+    /// Return the number of immutable memtables of `cf` that are not yet
+    /// flushed.
+    pub fn get_num_immutable_memtables_cf(&self, cf: &CFHandle) -> Option<u64> {
+        self.get_property_int_cf(cf, "rocksdb.num-immutable-mem-table")
+    }
+
+    /// Return the estimated memory used by the table readers of `cf`, not
+    /// including the blocks in the block cache.
+    pub fn get_table_readers_mem_cf(&self, cf: &CFHandle) -> Option<u64> {
+        self.get_property_int_cf(cf, "rocksdb.estimate-table-readers-mem")
+    }
+
+    /// Return the estimated bytes compaction needs to rewrite to get all
+    /// levels of `cf` down to under target size.
+    pub fn get_pending_compaction_bytes_cf(&self, cf: &CFHandle) -> Option<u64> {
+        self.get_property_int_cf(cf, "rocksdb.estimate-pending-compaction-bytes")
+    }
+
+    /// Return the estimated size of the live data of `cf` in bytes.
+    ///
+    /// Compare it with the size of the live sst files to monitor the space
+    /// amplification.
+    pub fn estimate_live_data_size_cf(&self, cf: &CFHandle) -> Option<u64> {
+        self.get_property_int_cf(cf, "rocksdb.estimate-live-data-size")
+    }
+
+    /// Return the estimated number of keys of `cf` in `[start, end)`.
+    ///
+    /// The keys in the sst files are estimated by scaling their number by
+    /// the share of the range in the approximate size of the live sst
+    /// files, and the keys in the memtables are counted from the memtable
+    /// stats of the range. No data is read.
+    ///
+    /// Returns 0 if `start` is not less than `end` in bytewise order.
+    pub fn estimate_num_keys_in_range_cf(&self,
+                                         cf: &CFHandle,
+                                         start: &[u8],
+                                         end: &[u8])
+                                         -> Result<u64, String> {
+        if start >= end {
+            return Ok(0);
+        }
+        let mut mem_keys = 0;
+        let mut mem_size = 0;
+        unsafe {
+            rocksdb_ffi::rocksdb_approximate_memtable_stats_cf(self.inner,
+                                                               cf.inner,
+                                                               start.as_ptr(),
+                                                               start.len() as size_t,
+                                                               end.as_ptr(),
+                                                               end.len() as size_t,
+                                                               &mut mem_keys,
+                                                               &mut mem_size);
+        }
+
+        let get = |name| self.get_property_int_cf(cf, name).unwrap_or(0);
+        let sst_size = get("rocksdb.live-sst-files-size");
+        if sst_size == 0 {
+            return Ok(mem_keys);
+        }
+        // The estimated number of keys includes the memtables.
+        let sst_keys = get("rocksdb.estimate-num-keys")
+            .saturating_sub(get("rocksdb.num-entries-active-mem-table"))
+            .saturating_sub(get("rocksdb.num-entries-imm-mem-tables"));
+        let range_size = self.get_approximate_sizes_cf(cf, &[Range::new(start, end)])?[0];
+        if range_size >= sst_size {
+            return Ok(sst_keys + mem_keys);
+        }
+        Ok((sst_keys as f64 * range_size as f64 / sst_size as f64) as u64 + mem_keys)
+    }
+
+    /// Return the memory used by the memtables, table readers and block
+    /// cache of `cf`.
+    pub fn get_cf_memory_usage(&self, cf: &CFHandle) -> CfMemoryUsage {
+        let get = |name| self.get_property_int_cf(cf, name).unwrap_or(0);
+        CfMemoryUsage {
+            mem_table_total: get("rocksdb.size-all-mem-tables"),
+            mem_table_unflushed: get("rocksdb.cur-size-all-mem-tables"),
+            mem_table_readers_total: self.get_table_readers_mem_cf(cf).unwrap_or(0),
+            cache_total: get("rocksdb.block-cache-usage"),
+        }
+    }
+
     fn get_property_value_cf_opt(&self, cf: Option<&CFHandle>, name: &str) -> Option<String> {
         unsafe {
             let prop_name = CString::new(name).unwrap();
@@ -806,6 +1605,20 @@ impl DB {
     pub fn get_statistics(&self) -> Option<String> {
         self.opts.get_statistics()
     }
+
+    pub fn get_statistics_ticker_count(&self, ticker: DBStatisticsTickerType) -> Option<u64> {
+        self.opts.get_statistics_ticker_count(ticker)
+    }
+
+    pub fn get_statistics_histogram(&self, hist_type: DBStatisticsHistogramType) -> HistogramData {
+        self.opts.get_statistics_histogram(hist_type)
+    }
+
+    pub fn get_statistics_histogram_string(&self,
+                                           hist_type: DBStatisticsHistogramType)
+                                           -> Option<String> {
+        self.opts.get_statistics_histogram_string(hist_type)
+    }
 }
 
 impl Writable for DB {
@@ -832,11 +1645,26 @@ impl Writable for DB {
     fn delete_cf(&self, cf: &CFHandle, key: &[u8]) -> Result<(), String> {
         self.delete_cf_opt(cf, key, &WriteOptions::new())
     }
+
+    fn delete_range(&self, begin_key: &[u8], end_key: &[u8]) -> Result<(), String> {
+        self.delete_range_opt(begin_key, end_key, &WriteOptions::new())
+    }
+
+    fn delete_range_cf(&self,
+                       cf: &CFHandle,
+                       begin_key: &[u8],
+                       end_key: &[u8])
+                       -> Result<(), String> {
+        self.delete_range_cf_opt(cf, begin_key, end_key, &WriteOptions::new())
+    }
 }
 
 impl Default for WriteBatch {
     fn default() -> WriteBatch {
-        WriteBatch { inner: unsafe { rocksdb_ffi::rocksdb_writebatch_create() } }
+        WriteBatch {
+            inner: unsafe { rocksdb_ffi::rocksdb_writebatch_create() },
+            max_bytes: 0,
+        }
     }
 }
 
@@ -852,6 +1680,244 @@ impl WriteBatch {
     pub fn is_empty(&self) -> bool {
         self.count() == 0
     }
+
+    /// Limit the size of the batch to `max_bytes`, 0 means unlimited.
+    ///
+    /// Once set, an update that would make `data_size` exceed the limit
+    /// is not added and returns an error instead, so the batch can be
+    /// written and a new one started.
+    pub fn set_max_bytes(&mut self, max_bytes: usize) {
+        self.max_bytes = max_bytes;
+    }
+
+    fn check_size<F: FnOnce()>(&self, update: F) -> Result<(), String> {
+        if self.max_bytes == 0 {
+            update();
+            return Ok(());
+        }
+        let size = self.data_size();
+        unsafe {
+            rocksdb_ffi::rocksdb_writebatch_set_save_point(self.inner);
+            update();
+            let new_size = self.data_size();
+            if new_size > self.max_bytes {
+                ffi_try!(rocksdb_writebatch_rollback_to_save_point(self.inner));
+                return Err(format!("write batch of {} bytes would grow to {} bytes, which \
+                                    exceeds the limit of {} bytes",
+                                   size,
+                                   new_size,
+                                   self.max_bytes));
+            }
+            ffi_try!(rocksdb_writebatch_pop_save_point(self.inner));
+        }
+        Ok(())
+    }
+
+    /// Add version `ts` of `key` into `cf`, which must use
+    /// `Options::set_comparator_with_u64_ts`, or an error is returned.
+    pub fn put_cf_with_ts(&self,
+                          cf: &CFHandle,
+                          key: &[u8],
+                          ts: u64,
+                          value: &[u8])
+                          -> Result<(), String> {
+        let ts = comparator::encode_u64_ts(ts);
+        let count = self.count();
+        self.check_size(|| unsafe {
+            rocksdb_ffi::rocksdb_writebatch_put_cf_with_ts(self.inner,
+                                                           cf.inner,
+                                                           key.as_ptr(),
+                                                           key.len() as size_t,
+                                                           ts.as_ptr(),
+                                                           ts.len() as size_t,
+                                                           value.as_ptr(),
+                                                           value.len() as size_t);
+        })?;
+        // The C API drops the status, a column family without a timestamp
+        // of the same size rejects the update and leaves the batch as is.
+        if self.count() == count {
+            return Err("the column family doesn't use the u64 timestamp comparator".to_owned());
+        }
+        Ok(())
+    }
+
+    /// Remove all the updates in the batch, keeping its buffer.
+    pub fn clear(&mut self) {
+        unsafe {
+            rocksdb_ffi::rocksdb_writebatch_clear(self.inner);
+        }
+    }
+
+    /// Append a blob of arbitrary size to the records in this batch.
+    ///
+    /// The blob is stored in the WAL along with the batch but is not
+    /// applied to the db, and it's not counted by `count`.
+    pub fn put_log_data(&mut self, blob: &[u8]) {
+        unsafe {
+            rocksdb_ffi::rocksdb_writebatch_put_log_data(self.inner,
+                                                         blob.as_ptr(),
+                                                         blob.len() as size_t);
+        }
+    }
+
+    /// Return the serialized batch, as written to the WAL.
+    pub fn data(&self) -> &[u8] {
+        let mut size = 0;
+        unsafe {
+            let data = rocksdb_ffi::rocksdb_writebatch_data(self.inner, &mut size);
+            slice::from_raw_parts(data, size)
+        }
+    }
+
+    /// Return the size of the updates in the serialized batch in bytes,
+    /// an empty batch reports 0.
+    ///
+    /// The header of the batch for the sequence number and count is not
+    /// counted.
+    pub fn data_size(&self) -> usize {
+        let mut size = 0;
+        unsafe {
+            rocksdb_ffi::rocksdb_writebatch_data(self.inner, &mut size);
+        }
+        size - WRITE_BATCH_HEADER_SIZE
+    }
+
+    /// Iterate over the records of the batch in the order they were
+    /// added, including the blobs added by `put_log_data`.
+    ///
+    /// The records are parsed from `data`, the column family of a record
+    /// is reported by its id, 0 is the default column family.
+    pub fn iter(&self) -> WriteBatchIter<'_> {
+        WriteBatchIter { data: &self.data()[WRITE_BATCH_HEADER_SIZE..] }
+    }
+}
+
+/// A record of a `WriteBatch`, see `WriteBatch::iter`.
+#[derive(Debug, PartialEq)]
+pub enum WriteBatchRecord<'a> {
+    Put {
+        cf: u32,
+        key: &'a [u8],
+        value: &'a [u8],
+    },
+    Merge {
+        cf: u32,
+        key: &'a [u8],
+        value: &'a [u8],
+    },
+    Delete { cf: u32, key: &'a [u8] },
+    SingleDelete { cf: u32, key: &'a [u8] },
+    DeleteRange {
+        cf: u32,
+        begin_key: &'a [u8],
+        end_key: &'a [u8],
+    },
+    LogData(&'a [u8]),
+}
+
+pub struct WriteBatchIter<'a> {
+    data: &'a [u8],
+}
+
+// The tags of the records in a serialized write batch.
+const TAG_DELETION: u8 = 0x0;
+const TAG_VALUE: u8 = 0x1;
+const TAG_MERGE: u8 = 0x2;
+const TAG_LOG_DATA: u8 = 0x3;
+const TAG_CF_DELETION: u8 = 0x4;
+const TAG_CF_VALUE: u8 = 0x5;
+const TAG_CF_MERGE: u8 = 0x6;
+const TAG_SINGLE_DELETION: u8 = 0x7;
+const TAG_CF_SINGLE_DELETION: u8 = 0x8;
+const TAG_CF_RANGE_DELETION: u8 = 0xE;
+const TAG_RANGE_DELETION: u8 = 0xF;
+
+impl<'a> WriteBatchIter<'a> {
+    fn read_varint32(&mut self) -> Result<u32, String> {
+        let mut res = 0;
+        for (i, &b) in self.data.iter().enumerate().take(5) {
+            res |= ((b & 0x7f) as u32) << (7 * i);
+            if b & 0x80 == 0 {
+                self.data = &self.data[i + 1..];
+                return Ok(res);
+            }
+        }
+        Err("bad varint32 in write batch".to_owned())
+    }
+
+    fn read_slice(&mut self) -> Result<&'a [u8], String> {
+        let len = self.read_varint32()? as usize;
+        if self.data.len() < len {
+            return Err("truncated record in write batch".to_owned());
+        }
+        let (slice, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(slice)
+    }
+
+    fn read_record(&mut self) -> Result<WriteBatchRecord<'a>, String> {
+        let tag = self.data[0];
+        self.data = &self.data[1..];
+        let cf = match tag {
+            TAG_CF_DELETION | TAG_CF_VALUE | TAG_CF_MERGE | TAG_CF_SINGLE_DELETION |
+            TAG_CF_RANGE_DELETION => self.read_varint32()?,
+            _ => 0,
+        };
+        let record = match tag {
+            TAG_VALUE | TAG_CF_VALUE => {
+                WriteBatchRecord::Put {
+                    cf,
+                    key: self.read_slice()?,
+                    value: self.read_slice()?,
+                }
+            }
+            TAG_MERGE | TAG_CF_MERGE => {
+                WriteBatchRecord::Merge {
+                    cf,
+                    key: self.read_slice()?,
+                    value: self.read_slice()?,
+                }
+            }
+            TAG_DELETION | TAG_CF_DELETION => {
+                WriteBatchRecord::Delete {
+                    cf,
+                    key: self.read_slice()?,
+                }
+            }
+            TAG_SINGLE_DELETION | TAG_CF_SINGLE_DELETION => {
+                WriteBatchRecord::SingleDelete {
+                    cf,
+                    key: self.read_slice()?,
+                }
+            }
+            TAG_RANGE_DELETION | TAG_CF_RANGE_DELETION => {
+                WriteBatchRecord::DeleteRange {
+                    cf,
+                    begin_key: self.read_slice()?,
+                    end_key: self.read_slice()?,
+                }
+            }
+            TAG_LOG_DATA => WriteBatchRecord::LogData(self.read_slice()?),
+            _ => return Err(format!("unknown record tag {} in write batch", tag)),
+        };
+        Ok(record)
+    }
+}
+
+impl<'a> Iterator for WriteBatchIter<'a> {
+    type Item = Result<WriteBatchRecord<'a>, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let res = self.read_record();
+        if res.is_err() {
+            // The rest of the batch can't be parsed.
+            self.data = &[];
+        }
+        Some(res)
+    }
 }
 
 impl Drop for WriteBatch {
@@ -862,8 +1928,12 @@ impl Drop for WriteBatch {
 
 impl Drop for DB {
     fn drop(&mut self) {
+        self.cfs.clear();
+        // Outstanding owned handles must not be destroyed concurrently
+        // with or after closing.
+        let mut closed = self.closed.lock().unwrap();
+        *closed = true;
         unsafe {
-            self.cfs.clear();
             rocksdb_ffi::rocksdb_close(self.inner);
         }
     }
@@ -871,67 +1941,183 @@ impl Drop for DB {
 
 impl Writable for WriteBatch {
     fn put(&self, key: &[u8], value: &[u8]) -> Result<(), String> {
-        unsafe {
+        self.check_size(|| unsafe {
             rocksdb_ffi::rocksdb_writebatch_put(self.inner,
                                                 key.as_ptr(),
                                                 key.len() as size_t,
                                                 value.as_ptr(),
                                                 value.len() as size_t);
-            Ok(())
-        }
+        })
     }
 
     fn put_cf(&self, cf: &CFHandle, key: &[u8], value: &[u8]) -> Result<(), String> {
-        unsafe {
+        self.check_size(|| unsafe {
             rocksdb_ffi::rocksdb_writebatch_put_cf(self.inner,
                                                    cf.inner,
                                                    key.as_ptr(),
                                                    key.len() as size_t,
                                                    value.as_ptr(),
                                                    value.len() as size_t);
-            Ok(())
-        }
+        })
     }
 
     fn merge(&self, key: &[u8], value: &[u8]) -> Result<(), String> {
-        unsafe {
+        self.check_size(|| unsafe {
             rocksdb_ffi::rocksdb_writebatch_merge(self.inner,
                                                   key.as_ptr(),
                                                   key.len() as size_t,
                                                   value.as_ptr(),
                                                   value.len() as size_t);
-            Ok(())
-        }
+        })
     }
 
     fn merge_cf(&self, cf: &CFHandle, key: &[u8], value: &[u8]) -> Result<(), String> {
-        unsafe {
+        self.check_size(|| unsafe {
             rocksdb_ffi::rocksdb_writebatch_merge_cf(self.inner,
                                                      cf.inner,
                                                      key.as_ptr(),
                                                      key.len() as size_t,
                                                      value.as_ptr(),
                                                      value.len() as size_t);
-            Ok(())
-        }
+        })
     }
 
     fn delete(&self, key: &[u8]) -> Result<(), String> {
-        unsafe {
+        self.check_size(|| unsafe {
             rocksdb_ffi::rocksdb_writebatch_delete(self.inner, key.as_ptr(), key.len() as size_t);
-            Ok(())
-        }
+        })
     }
 
     fn delete_cf(&self, cf: &CFHandle, key: &[u8]) -> Result<(), String> {
-        unsafe {
+        self.check_size(|| unsafe {
             rocksdb_ffi::rocksdb_writebatch_delete_cf(self.inner,
                                                       cf.inner,
                                                       key.as_ptr(),
                                                       key.len() as size_t);
+        })
+    }
+
+    fn delete_range(&self, begin_key: &[u8], end_key: &[u8]) -> Result<(), String> {
+        self.check_size(|| unsafe {
+            rocksdb_ffi::rocksdb_writebatch_delete_range(self.inner,
+                                                         begin_key.as_ptr(),
+                                                         begin_key.len(),
+                                                         end_key.as_ptr(),
+                                                         end_key.len());
+        })
+    }
+
+    fn delete_range_cf(&self,
+                       cf: &CFHandle,
+                       begin_key: &[u8],
+                       end_key: &[u8])
+                       -> Result<(), String> {
+        self.check_size(|| unsafe {
+            rocksdb_ffi::rocksdb_writebatch_delete_range_cf(self.inner,
+                                                            cf.inner,
+                                                            begin_key.as_ptr(),
+                                                            begin_key.len(),
+                                                            end_key.as_ptr(),
+                                                            end_key.len());
+        })
+    }
+}
+
+/// `SstFileWriter` is used to create sst files that can be added to the db
+/// later with `DB::ingest_external_file`.
+///
+/// The keys must be added in the order of the comparator in the options.
+pub struct SstFileWriter {
+    inner: *mut DBSstFileWriter,
+    // The options must outlive the writer.
+    _env_opt: EnvOptions,
+    _opt: Options,
+}
+
+unsafe impl Send for SstFileWriter {}
+
+impl SstFileWriter {
+    /// Create a writer that builds sst files with `opt`.
+    ///
+    /// `opt` should be the options of the column family the files will be
+    /// ingested into, so the files use the same comparator and compression.
+    /// Ingesting a file built with a different comparator fails.
+    pub fn new(env_opt: EnvOptions, opt: Options) -> SstFileWriter {
+        unsafe {
+            SstFileWriter {
+                inner: rocksdb_ffi::rocksdb_sstfilewriter_create(env_opt.inner, opt.inner),
+                _env_opt: env_opt,
+                _opt: opt,
+            }
+        }
+    }
+
+    /// Prepare the writer to write an sst file at `name`.
+    pub fn open(&mut self, name: &str) -> Result<(), String> {
+        let path = match CString::new(name.as_bytes()) {
+            Err(e) => return Err(format!("invalid path {}: {:?}", name, e)),
+            Ok(p) => p,
+        };
+        unsafe {
+            ffi_try!(rocksdb_sstfilewriter_open(self.inner, path.as_ptr()));
+            Ok(())
+        }
+    }
+
+    /// Add a Put key with value to currently opened file.
+    pub fn put(&mut self, key: &[u8], val: &[u8]) -> Result<(), String> {
+        unsafe {
+            ffi_try!(rocksdb_sstfilewriter_put(self.inner,
+                                               key.as_ptr(),
+                                               key.len() as size_t,
+                                               val.as_ptr(),
+                                               val.len() as size_t));
+            Ok(())
+        }
+    }
+
+    /// Add a Merge key with value to currently opened file.
+    pub fn merge(&mut self, key: &[u8], val: &[u8]) -> Result<(), String> {
+        unsafe {
+            ffi_try!(rocksdb_sstfilewriter_merge(self.inner,
+                                                 key.as_ptr(),
+                                                 key.len() as size_t,
+                                                 val.as_ptr(),
+                                                 val.len() as size_t));
+            Ok(())
+        }
+    }
+
+    /// Add a deletion key to currently opened file.
+    pub fn delete(&mut self, key: &[u8]) -> Result<(), String> {
+        unsafe {
+            ffi_try!(rocksdb_sstfilewriter_delete(self.inner, key.as_ptr(), key.len() as size_t));
             Ok(())
         }
     }
+
+    /// Finalize writing to the sst file and close it.
+    pub fn finish(&mut self) -> Result<(), String> {
+        unsafe {
+            ffi_try!(rocksdb_sstfilewriter_finish(self.inner));
+            Ok(())
+        }
+    }
+
+    /// Return the current size of the file being written.
+    pub fn file_size(&mut self) -> u64 {
+        let mut size = 0;
+        unsafe {
+            rocksdb_ffi::rocksdb_sstfilewriter_file_size(self.inner, &mut size);
+        }
+        size
+    }
+}
+
+impl Drop for SstFileWriter {
+    fn drop(&mut self) {
+        unsafe { rocksdb_ffi::rocksdb_sstfilewriter_destroy(self.inner) }
+    }
 }
 
 pub struct DBVector {
@@ -965,12 +2151,55 @@ impl DBVector {
     pub fn to_utf8(&self) -> Option<&str> {
         from_utf8(self.deref()).ok()
     }
+
+    /// Copy the value into an owned `Vec<u8>`.
+    ///
+    /// The value is allocated by rocksdb, so it can't be turned into a
+    /// `Vec<u8>` without copying.
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.deref().to_vec()
+    }
+}
+
+// The value is not shared with rocksdb once it's returned.
+unsafe impl Send for DBVector {}
+unsafe impl Sync for DBVector {}
+
+impl AsRef<[u8]> for DBVector {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl Borrow<[u8]> for DBVector {
+    fn borrow(&self) -> &[u8] {
+        self
+    }
+}
+
+impl PartialEq<&[u8]> for DBVector {
+    fn eq(&self, rhs: &&[u8]) -> bool {
+        self.deref() == *rhs
+    }
+}
+
+impl fmt::Debug for DBVector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "b\"")?;
+        for &b in self.deref() {
+            for c in ascii::escape_default(b) {
+                write!(f, "{}", c as char)?;
+            }
+        }
+        write!(f, "\"")
+    }
 }
 
 #[cfg(test)]
 mod test {
     use rocksdb_options::*;
     use std::str;
+    use std::thread;
     use super::*;
     use tempdir::TempDir;
 
@@ -1000,6 +2229,51 @@ mod test {
         }
     }
 
+    #[test]
+    fn destroy_test() {
+        let path = TempDir::new("_rust_rocksdb_destroy_test").expect("");
+        let path_str = path.path().to_str().unwrap();
+        {
+            let db = DB::open_default(path_str).unwrap();
+            db.put(b"k1", b"v1").unwrap();
+            db.flush(true).unwrap();
+        }
+        assert!(Path::new(path_str).join("CURRENT").exists());
+
+        let opts = Options::new();
+        DB::destroy(&opts, path_str).unwrap();
+        if let Ok(entries) = fs::read_dir(path_str) {
+            assert_eq!(entries.count(), 0);
+        }
+    }
+
+    #[test]
+    fn repair_test() {
+        let path = TempDir::new("_rust_rocksdb_repair_test").expect("");
+        let path_str = path.path().to_str().unwrap();
+        {
+            let db = DB::open_default(path_str).unwrap();
+            db.put(b"k1", b"v1").unwrap();
+            db.put(b"k2", b"v2").unwrap();
+            db.flush(true).unwrap();
+        }
+
+        // Corrupt all the MANIFEST files.
+        for entry in fs::read_dir(path_str).unwrap() {
+            let p = entry.unwrap().path();
+            if p.file_name().unwrap().to_str().unwrap().starts_with("MANIFEST") {
+                fs::write(&p, b"corrupted").unwrap();
+            }
+        }
+        assert!(DB::open_default(path_str).is_err());
+
+        let opts = Options::new();
+        DB::repair(&opts, path_str).unwrap();
+        let db = DB::open_default(path_str).unwrap();
+        assert_eq!(db.get(b"k1").unwrap().unwrap().to_utf8().unwrap(), "v1");
+        assert_eq!(db.get(b"k2").unwrap().unwrap().to_utf8().unwrap(), "v2");
+    }
+
     #[test]
     fn writebatch_works() {
         let path = TempDir::new("_rust_rocksdb_writebacktest").expect("");
@@ -1029,6 +2303,167 @@ mod test {
         assert!(db.get(b"k1").unwrap().is_none());
     }
 
+    #[test]
+    fn writebatch_size_test() {
+        let batch = WriteBatch::new();
+        assert_eq!(batch.count(), 0);
+        assert_eq!(batch.data_size(), 0);
+        assert_eq!(batch.data().len(), 12);
+        batch.put(b"k1", b"v1").unwrap();
+        let one_put = batch.data_size();
+        assert!(one_put > 0);
+        assert_eq!(batch.data().len(), one_put + 12);
+        batch.put(b"k2", b"v2").unwrap();
+        batch.put(b"k3", b"v3").unwrap();
+        batch.delete(b"k1").unwrap();
+        assert_eq!(batch.count(), 4);
+        assert!(batch.data_size() > one_put);
+    }
+
+    #[test]
+    fn writebatch_clear_test() {
+        let path = TempDir::new("_rust_rocksdb_writebatch_clear").expect("");
+        let db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+
+        let mut batch = WriteBatch::new();
+        batch.put(b"k1", b"v1").unwrap();
+        batch.put(b"k2", b"v2").unwrap();
+        batch.clear();
+        assert_eq!(batch.count(), 0);
+        assert_eq!(batch.data_size(), 0);
+        batch.put(b"k3", b"v3").unwrap();
+        batch.put(b"k4", b"v4").unwrap();
+        assert_eq!(batch.count(), 2);
+        db.write(batch).unwrap();
+
+        assert!(db.get(b"k1").unwrap().is_none());
+        assert!(db.get(b"k2").unwrap().is_none());
+        assert_eq!(&*db.get(b"k3").unwrap().unwrap(), b"v3");
+        assert_eq!(&*db.get(b"k4").unwrap().unwrap(), b"v4");
+    }
+
+    #[test]
+    fn writebatch_put_log_data_test() {
+        let path = TempDir::new("_rust_rocksdb_writebatch_put_log_data").expect("");
+        let db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+
+        let blob = b"txn-id:42";
+        let mut batch = WriteBatch::new();
+        batch.put(b"k1", b"v1").unwrap();
+        let size = batch.data_size();
+        batch.put_log_data(blob);
+        assert_eq!(batch.count(), 1);
+        assert!(batch.data_size() > size + blob.len());
+        assert!(batch.data().windows(blob.len()).any(|w| w == blob));
+        db.write(batch).unwrap();
+
+        assert_eq!(&*db.get(b"k1").unwrap().unwrap(), b"v1");
+        assert!(db.get(blob).unwrap().is_none());
+        let mut iter = db.iter();
+        iter.seek(SeekKey::Start);
+        assert_eq!(iter.count(), 1);
+    }
+
+    #[test]
+    fn writebatch_iter_test() {
+        let path = TempDir::new("_rust_rocksdb_writebatch_iter").expect("");
+        let mut db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+        db.create_cf("cf1", &Options::new()).unwrap();
+        let cf1 = db.cf_handle("cf1").unwrap();
+
+        let mut batch = WriteBatch::new();
+        assert_eq!(batch.iter().count(), 0);
+        batch.put(b"k1", b"v1").unwrap();
+        batch.put_log_data(b"txn-id:42");
+        batch.put_cf(cf1, b"k2", &[b'v'; 200]).unwrap();
+        batch.merge(b"k3", b"v3").unwrap();
+        batch.delete_cf(cf1, b"k1").unwrap();
+        batch.delete_range(b"k4", b"k5").unwrap();
+        batch.put_log_data(b"");
+        let records: Vec<_> = batch.iter().map(|r| r.unwrap()).collect();
+        assert_eq!(records,
+                   vec![WriteBatchRecord::Put {
+                            cf: 0,
+                            key: b"k1",
+                            value: b"v1",
+                        },
+                        WriteBatchRecord::LogData(b"txn-id:42"),
+                        WriteBatchRecord::Put {
+                            cf: 1,
+                            key: b"k2",
+                            value: &[b'v'; 200],
+                        },
+                        WriteBatchRecord::Merge {
+                            cf: 0,
+                            key: b"k3",
+                            value: b"v3",
+                        },
+                        WriteBatchRecord::Delete { cf: 1, key: b"k1" },
+                        WriteBatchRecord::DeleteRange {
+                            cf: 0,
+                            begin_key: b"k4",
+                            end_key: b"k5",
+                        },
+                        WriteBatchRecord::LogData(b"")]);
+
+        // A truncated batch reports an error and stops.
+        let data = batch.data();
+        let truncated = WriteBatchIter { data: &data[WRITE_BATCH_HEADER_SIZE..20] };
+        let records: Vec<_> = truncated.collect();
+        assert_eq!(records.len(), 2);
+        assert!(records[0].is_ok());
+        assert!(records[1].is_err());
+    }
+
+    #[test]
+    fn writebatch_max_bytes_test() {
+        let path = TempDir::new("_rust_rocksdb_writebatch_max_bytes").expect("");
+        let db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+
+        let mut batch = WriteBatch::new();
+        batch.set_max_bytes(1024);
+        let value = [b'v'; 100];
+        let mut added = 0;
+        let err = loop {
+            let k = format!("k{:04}", added);
+            match batch.put(k.as_bytes(), &value) {
+                Ok(()) => added += 1,
+                Err(e) => break e,
+            }
+        };
+        assert!(added > 0);
+
+        // The error names the current size and the limit.
+        let size = batch.data_size();
+        assert!(size <= 1024);
+        let prefix = format!("write batch of {} bytes would grow to ", size);
+        assert!(err.starts_with(&prefix), "{}", err);
+        assert!(err.ends_with(" bytes, which exceeds the limit of 1024 bytes"), "{}", err);
+        let new_size: usize = err[prefix.len()..].split(' ').next().unwrap().parse().unwrap();
+        assert!(new_size > 1024, "{}", err);
+
+        // The entries before the limit are kept as they are.
+        assert_eq!(batch.count(), added);
+        let keys: Vec<_> = (0..added).map(|i| format!("k{:04}", i)).collect();
+        let records: Vec<_> = batch.iter().map(|r| r.unwrap()).collect();
+        let expected: Vec<_> = keys.iter()
+            .map(|k| {
+                WriteBatchRecord::Put {
+                    cf: 0,
+                    key: k.as_bytes(),
+                    value: &value,
+                }
+            })
+            .collect();
+        assert_eq!(records, expected);
+
+        db.write(batch).unwrap();
+        for k in &keys {
+            assert_eq!(db.get(k.as_bytes()).unwrap().unwrap(), &value[..]);
+        }
+        assert!(db.get(format!("k{:04}", added).as_bytes()).unwrap().is_none());
+    }
+
     #[test]
     fn iterator_test() {
         let path = TempDir::new("_rust_rocksdb_iteratortest").expect("");
@@ -1062,7 +2497,8 @@ mod test {
                                                Range::new(b"2000", b"4000"),
                                                Range::new(b"4000", b"6000"),
                                                Range::new(b"6000", b"8000"),
-                                               Range::new(b"8000", b"9999")]);
+                                               Range::new(b"8000", b"9999")])
+            .unwrap();
         assert_eq!(sizes.len(), 5);
         for s in &sizes[0..4] {
             assert!(*s > 0);
@@ -1070,6 +2506,97 @@ mod test {
         assert_eq!(sizes[4], 0);
     }
 
+    #[test]
+    fn estimate_num_keys_in_range_test() {
+        let path = TempDir::new("_rust_rocksdb_estimate_num_keys_in_range").expect("");
+        let db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+        let cf = db.cf_handle("default").unwrap();
+        for i in 0..100000 {
+            let k = format!("{:06}", i);
+            db.put(k.as_bytes(), k.as_bytes()).unwrap();
+        }
+        db.flush(true).unwrap();
+
+        let estimate = |start: &[u8], end: &[u8]| {
+            db.estimate_num_keys_in_range_cf(cf, start, end).unwrap()
+        };
+        let all = estimate(b"", b"999999");
+        assert!(all >= 50000 && all <= 200000, "{}", all);
+        for &(start, end) in &[(b"000000", b"050000"), (b"050000", b"100000")] {
+            let half = estimate(start, end);
+            assert!(half >= 25000 && half <= 100000, "{}", half);
+        }
+        assert!(estimate(b"100000", b"999999") < 1000);
+        assert_eq!(estimate(b"050000", b"050000"), 0);
+        assert_eq!(estimate(b"050000", b"000000"), 0);
+    }
+
+    #[test]
+    fn estimate_num_keys_in_range_memtable_test() {
+        let path = TempDir::new("_rust_rocksdb_estimate_num_keys_in_range_memtable").expect("");
+        let db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+        let cf = db.cf_handle("default").unwrap();
+        for i in 0..10000 {
+            let k = format!("{:06}", i);
+            db.put(k.as_bytes(), k.as_bytes()).unwrap();
+        }
+
+        // Nothing is flushed, all the keys are in the memtable.
+        let estimate = |start: &[u8], end: &[u8]| {
+            db.estimate_num_keys_in_range_cf(cf, start, end).unwrap()
+        };
+        let all = estimate(b"", b"999999");
+        assert!(all >= 5000 && all <= 20000, "{}", all);
+        let half = estimate(b"000000", b"005000");
+        assert!(half >= 2500 && half <= 10000, "{}", half);
+        assert!(estimate(b"010000", b"999999") < 1000);
+        assert_eq!(estimate(b"005000", b"005000"), 0);
+    }
+
+    #[test]
+    fn estimate_live_data_size_test() {
+        let path = TempDir::new("_rust_rocksdb_estimate_live_data_size").expect("");
+        let mut opts = Options::new();
+        opts.create_if_missing(true);
+        opts.set_disable_auto_compactions(true);
+        let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+        let cf = db.cf_handle("default").unwrap();
+        for round in 0..4 {
+            for i in 0..1000 {
+                let v = format!("{:0100}", round);
+                db.put(format!("k{:04}", i).as_bytes(), v.as_bytes()).unwrap();
+            }
+            db.flush(true).unwrap();
+        }
+
+        let live = db.estimate_live_data_size_cf(cf).unwrap();
+        let total = db.get_property_int_cf(cf, "rocksdb.live-sst-files-size").unwrap();
+        assert!(live > 0);
+        assert!(live < total, "{} {}", live, total);
+    }
+
+    #[test]
+    fn path_test() {
+        let path = TempDir::new("_rust_rocksdb_path_test").expect("");
+        let path_str = path.path().to_str().unwrap();
+        let db = DB::open_default(path_str).unwrap();
+        assert_eq!(db.path(), path_str);
+    }
+
+    #[test]
+    fn range_test() {
+        let r = Range::new(b"k1", b"k2");
+        assert_eq!((r.start_key, r.end_key), (&b"k1"[..], &b"k2"[..]));
+        let r = Range::new(b"k1", b"k1");
+        assert_eq!(r.start_key, r.end_key);
+    }
+
+    #[test]
+    #[should_panic(expected = "is greater than end_key")]
+    fn reversed_range_test() {
+        Range::new(b"k2", b"k1");
+    }
+
     #[test]
     fn property_test() {
         let path = TempDir::new("_rust_rocksdb_propertytest").expect("");
@@ -1085,6 +2612,135 @@ mod test {
         assert!(st2 > st1);
     }
 
+    #[test]
+    fn cancel_all_background_work_test() {
+        let path = TempDir::new("_rust_rocksdb_cancel_all_background_work").expect("");
+        let db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+        for i in 0..1000 {
+            db.put(format!("k{}", i).as_bytes(), b"v").unwrap();
+            if i % 100 == 0 {
+                db.flush(false).unwrap();
+            }
+        }
+        db.cancel_all_background_work(true);
+        db.put(b"k", b"v").unwrap();
+        assert_eq!(db.get(b"k").unwrap().unwrap(), &b"v"[..]);
+        assert!(db.get(b"k999").unwrap().is_some());
+    }
+
+    #[test]
+    fn cf_memory_usage_test() {
+        let path = TempDir::new("_rust_rocksdb_cf_memory_usage_test").expect("");
+        let mut opts = Options::new();
+        opts.create_if_missing(true);
+        let mut db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+        db.create_cf("cf1", &Options::new()).unwrap();
+        let cf1 = db.cf_handle("cf1").unwrap();
+        let empty = db.get_cf_memory_usage(cf1);
+        for i in 0..100 {
+            db.put_cf(cf1, format!("k{}", i).as_bytes(), b"v").unwrap();
+        }
+        let usage = db.get_cf_memory_usage(cf1);
+        assert!(usage.mem_table_unflushed > empty.mem_table_unflushed);
+        assert!(usage.mem_table_total >= usage.mem_table_unflushed);
+    }
+
+    #[test]
+    fn single_key_write_opt_test() {
+        let path = TempDir::new("_rust_rocksdb_single_key_write_opt_test").expect("");
+        let batch_path = TempDir::new("_rust_rocksdb_single_key_write_opt_batch").expect("");
+        let db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+        let batch_db = DB::open_default(batch_path.path().to_str().unwrap()).unwrap();
+        let mut writeopts = WriteOptions::new();
+        writeopts.disable_wal(true);
+
+        let n = 10000;
+        let batch = WriteBatch::new();
+        for i in 0..n {
+            let k = format!("k{:07}", i);
+            let v = format!("v{}", i);
+            db.put_opt(k.as_bytes(), v.as_bytes(), &writeopts).unwrap();
+            batch.put(k.as_bytes(), v.as_bytes()).unwrap();
+        }
+        for i in (0..n).filter(|i| i % 3 == 0) {
+            let k = format!("k{:07}", i);
+            db.delete_opt(k.as_bytes(), &writeopts).unwrap();
+            batch.delete(k.as_bytes()).unwrap();
+        }
+        batch_db.write_opt(batch, &writeopts).unwrap();
+
+        let mut iter = db.iter();
+        iter.seek(SeekKey::Start);
+        let mut batch_iter = batch_db.iter();
+        batch_iter.seek(SeekKey::Start);
+        let mut count = 0;
+        for (kv, batch_kv) in iter.zip(&mut batch_iter) {
+            assert_eq!(kv, batch_kv);
+            count += 1;
+        }
+        assert!(!batch_iter.valid());
+        assert_eq!(count, n - (n + 2) / 3);
+    }
+
+    #[test]
+    fn approximate_memory_usage_by_type_test() {
+        let path = TempDir::new("_rust_rocksdb_approximate_memory_usage_by_type").expect("");
+        let db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+        for i in 0..100 {
+            db.put(format!("k{}", i).as_bytes(), b"v").unwrap();
+        }
+        let usage = get_approximate_memory_usage_by_type(&[&db]).unwrap();
+        let cf = db.cf_handle("default").unwrap();
+        let cf_usage = db.get_cf_memory_usage(cf);
+        assert_eq!(usage[&MemoryUsageType::MemTableTotal], cf_usage.mem_table_total);
+        assert_eq!(usage[&MemoryUsageType::MemTableUnflushed],
+                   cf_usage.mem_table_unflushed);
+        assert!(usage[&MemoryUsageType::MemTableUnflushed] > 0);
+    }
+
+    #[test]
+    fn typed_property_test() {
+        let path = TempDir::new("_rust_rocksdb_typed_property_test").expect("");
+        let db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+        let cf = db.cf_handle("default").unwrap();
+        assert_eq!(db.get_num_immutable_memtables_cf(cf), Some(0));
+        let empty = db.get_memtable_size_cf(cf).unwrap();
+        for i in 0..100 {
+            db.put(format!("k{}", i).as_bytes(), b"v").unwrap();
+        }
+        assert!(db.get_memtable_size_cf(cf).unwrap() > empty);
+        db.flush(true).unwrap();
+        assert_eq!(db.get_num_immutable_memtables_cf(cf), Some(0));
+        assert!(db.get_pending_compaction_bytes_cf(cf).is_some());
+
+        db.get(b"k1").unwrap().unwrap();
+        assert!(db.get_block_cache_usage().unwrap() > 0);
+    }
+
+    #[test]
+    fn aggregated_int_property_test() {
+        let path = TempDir::new("_rust_rocksdb_aggregated_int_property").expect("");
+        let mut db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+        db.create_cf("cf1", &Options::new()).unwrap();
+        let default = db.cf_handle("default").unwrap();
+        let cf1 = db.cf_handle("cf1").unwrap();
+        for i in 0..100 {
+            db.put_cf(default, format!("k{}", i).as_bytes(), b"v").unwrap();
+        }
+        for i in 0..50 {
+            db.put_cf(cf1, format!("k{}", i).as_bytes(), b"value").unwrap();
+        }
+
+        for name in &["rocksdb.cur-size-all-mem-tables", "rocksdb.num-entries-active-mem-table"] {
+            let sum = db.get_property_int_cf(default, name).unwrap() +
+                      db.get_property_int_cf(cf1, name).unwrap();
+            assert_eq!(db.get_aggregated_int_property(name), Some(sum));
+        }
+        assert_eq!(db.get_aggregated_int_property("rocksdb.num-entries-active-mem-table"),
+                   Some(150));
+        assert_eq!(db.get_aggregated_int_property("rocksdb.stats"), None);
+    }
+
     #[test]
     fn list_column_families_test() {
         let path = TempDir::new("_rust_rocksdb_list_column_families_test").expect("");
@@ -1113,6 +2769,43 @@ mod test {
         cfs.sort();
         assert_eq!(cfs_vec, cfs);
     }
+
+    #[test]
+    fn dbvector_test() {
+        let path = TempDir::new("_rust_rocksdb_dbvector_test").expect("");
+        let db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        db.put(b"k2", b"\xff\x00\n").unwrap();
+
+        let v1 = db.get(b"k1").unwrap().unwrap();
+        assert_eq!(v1, &b"v1"[..]);
+        assert!(v1 != &b"v2"[..]);
+        assert_eq!(v1.as_ref(), b"v1");
+        assert_eq!(v1.to_vec(), b"v1".to_vec());
+        assert_eq!(v1.to_utf8(), Some("v1"));
+        assert_eq!(format!("{:?}", v1), "b\"v1\"");
+
+        let v2 = db.get(b"k2").unwrap().unwrap();
+        assert_eq!(v2, &b"\xff\x00\n"[..]);
+        assert_eq!(v2.to_utf8(), None);
+        assert_eq!(format!("{:?}", v2), "b\"\\xff\\x00\\n\"");
+
+        // A multi-byte character is valid UTF-8, a truncated one is not.
+        db.put(b"k3", "v\u{e9}".as_bytes()).unwrap();
+        db.put(b"k4", b"v\xc3").unwrap();
+        assert_eq!(db.get(b"k3").unwrap().unwrap().to_utf8(), Some("v\u{e9}"));
+        assert_eq!(db.get(b"k4").unwrap().unwrap().to_utf8(), None);
+
+        // v1 is dropped in another thread, v2 is read there and sent back.
+        let v2 = thread::spawn(move || {
+                assert_eq!(v1, &b"v1"[..]);
+                assert_eq!(v2.to_utf8(), None);
+                v2
+            })
+            .join()
+            .unwrap();
+        assert_eq!(v2, &b"\xff\x00\n"[..]);
+    }
 }
 
 #[test]
@@ -1135,6 +2828,17 @@ fn snapshot_test() {
 
         assert!(db.get(b"k2").unwrap().is_some());
         assert!(snap.get(b"k2").unwrap().is_none());
+
+        let seqno = snap.get_sequence_number();
+        assert!(seqno > 0);
+        let snap2 = db.snapshot();
+        assert_eq!(snap2.get_sequence_number(), seqno + 1);
+        assert_eq!(snap.get_sequence_number(), seqno);
+        unsafe {
+            let snap3 = db.unsafe_snap();
+            assert_eq!(snap3.get_sequence_number(), seqno + 1);
+            db.release_snap(&snap3);
+        }
     }
     let opts = Options::new();
     assert!(DB::destroy(&opts, path).is_ok());
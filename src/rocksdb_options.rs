@@ -15,15 +15,22 @@
 
 use compaction_filter::{CompactionFilter, new_compaction_filter, CompactionFilterHandle};
 use comparator::{self, ComparatorCallback, compare_callback};
-use libc::{self, c_int, size_t, c_void};
+use libc::{self, c_char, c_int, size_t, c_void};
+use logger::{Logger, LoggerHandle, new_logger};
 use merge_operator::{self, MergeOperatorCallback, full_merge_callback, partial_merge_callback};
 use merge_operator::MergeFn;
 
 use rocksdb_ffi::{self, DBOptions, DBWriteOptions, DBBlockBasedTableOptions, DBReadOptions,
                   DBCompressionType, DBRecoveryMode, DBSnapshot, DBInstance, DBFlushOptions,
-                  DBRateLimiter};
+                  DBRateLimiter, DBChecksumType, DBDataBlockIndexType, DBEnv, DBInfoLogLevel,
+                  DBPrepopulateBlockCache,
+                  DBRateLimiterMode, DBEnvOptions, DBIngestExternalFileOptions, DBHistogramData,
+                  DBStatisticsHistogramType, DBStatisticsTickerType};
 use std::ffi::{CStr, CString};
 use std::mem;
+use std::ptr;
+use std::slice;
+use std::sync::Arc;
 
 pub struct BlockBasedOptions {
     inner: *mut DBBlockBasedTableOptions,
@@ -59,6 +66,49 @@ impl BlockBasedOptions {
         }
     }
 
+    pub fn set_block_size_deviation(&mut self, deviation: c_int) {
+        unsafe {
+            rocksdb_ffi::rocksdb_block_based_options_set_block_size_deviation(self.inner, deviation);
+        }
+    }
+
+    /// Disable block cache, every read goes to the table files.
+    pub fn set_no_block_cache(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_block_based_options_set_no_block_cache(self.inner, v);
+        }
+    }
+
+    pub fn set_block_restart_interval(&mut self, interval: c_int) {
+        unsafe {
+            rocksdb_ffi::rocksdb_block_based_options_set_block_restart_interval(self.inner,
+                                                                                interval);
+        }
+    }
+
+    pub fn set_index_block_restart_interval(&mut self, interval: c_int) {
+        unsafe {
+            rocksdb_ffi::rocksdb_block_based_options_set_index_block_restart_interval(self.inner,
+                                                                                      interval);
+        }
+    }
+
+    /// Set the on-disk format version of newly created tables.
+    ///
+    /// Unsupported versions are rejected when the db is opened.
+    pub fn set_format_version(&mut self, version: u32) {
+        unsafe {
+            rocksdb_ffi::rocksdb_block_based_options_set_format_version(self.inner,
+                                                                        version as c_int);
+        }
+    }
+
+    pub fn set_checksum(&mut self, t: DBChecksumType) {
+        unsafe {
+            rocksdb_ffi::rocksdb_block_based_options_set_checksum(self.inner, t as c_char);
+        }
+    }
+
     pub fn set_lru_cache(&mut self, size: size_t) {
         let cache = rocksdb_ffi::new_cache(size);
         unsafe {
@@ -68,24 +118,149 @@ impl BlockBasedOptions {
         }
     }
 
+    /// Set how keys are searched inside a data block.
+    ///
+    /// `BinaryAndHash` appends a hash index to every data block, which
+    /// speeds up point lookups at the cost of some space.
+    pub fn set_data_block_index_type(&mut self, t: DBDataBlockIndexType) {
+        unsafe {
+            rocksdb_ffi::rocksdb_block_based_options_set_data_block_index_type(self.inner, t);
+        }
+    }
+
+    /// Insert the blocks written by flushes into the block cache, so the
+    /// freshly flushed data doesn't miss the cache when read back.
+    pub fn set_prepopulate_block_cache(&mut self, v: DBPrepopulateBlockCache) {
+        unsafe {
+            rocksdb_ffi::rocksdb_block_based_options_set_prepopulate_block_cache(self.inner, v);
+        }
+    }
+
+    /// The utilization ratio of the data block hash index, only works with
+    /// `DBDataBlockIndexType::BinaryAndHash`.
+    pub fn set_data_block_hash_table_util_ratio(&mut self, ratio: f64) {
+        unsafe {
+            rocksdb_ffi::rocksdb_block_based_options_set_data_block_hash_ratio(self.inner, ratio);
+        }
+    }
+
     pub fn set_bloom_filter(&mut self, bits_per_key: c_int, block_based: bool) {
         unsafe {
             let bloom = if block_based {
-                rocksdb_ffi::rocksdb_filterpolicy_create_bloom(bits_per_key)
+                rocksdb_ffi::rocksdb_filterpolicy_create_bloom(bits_per_key as f64)
             } else {
-                rocksdb_ffi::rocksdb_filterpolicy_create_bloom_full(bits_per_key)
+                rocksdb_ffi::rocksdb_filterpolicy_create_bloom_full(bits_per_key as f64)
             };
 
             rocksdb_ffi::rocksdb_block_based_options_set_filter_policy(self.inner, bloom);
         }
     }
 
+    /// Use a ribbon filter, which takes about 30% less memory than a bloom
+    /// filter with the same false positive rate, but costs more CPU to build.
+    pub fn set_ribbon_filter(&mut self, bloom_equivalent_bits_per_key: f64) {
+        unsafe {
+            let ribbon =
+                rocksdb_ffi::rocksdb_filterpolicy_create_ribbon(bloom_equivalent_bits_per_key);
+            rocksdb_ffi::rocksdb_block_based_options_set_filter_policy(self.inner, ribbon);
+        }
+    }
+
+    /// Use bloom filters for flushes and levels before `bloom_before_level`,
+    /// and ribbon filters for the rest.
+    pub fn set_hybrid_ribbon_filter(&mut self,
+                                    bloom_equivalent_bits_per_key: f64,
+                                    bloom_before_level: c_int) {
+        unsafe {
+            let ribbon =
+                rocksdb_ffi::rocksdb_filterpolicy_create_ribbon_hybrid(bloom_equivalent_bits_per_key,
+                                                                       bloom_before_level);
+            rocksdb_ffi::rocksdb_block_based_options_set_filter_policy(self.inner, ribbon);
+        }
+    }
+
     pub fn set_cache_index_and_filter_blocks(&mut self, v: bool) {
         unsafe {
             rocksdb_ffi::rocksdb_block_based_options_set_cache_index_and_filter_blocks(self.inner,
                                                                                        v as u8);
         }
     }
+
+    pub fn set_pin_l0_filter_and_index_blocks_in_cache(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_block_based_options_set_pin_l0_filter_and_index_blocks_in_cache(
+                self.inner, v as u8);
+        }
+    }
+
+    pub fn set_pin_top_level_index_and_filter(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_block_based_options_set_pin_top_level_index_and_filter(self.inner,
+                                                                                        v as u8);
+        }
+    }
+}
+
+/// A snapshot of a statistics histogram, see
+/// `Options::get_statistics_histogram`.
+pub struct HistogramData {
+    inner: *mut DBHistogramData,
+}
+
+impl Drop for HistogramData {
+    fn drop(&mut self) {
+        unsafe {
+            rocksdb_ffi::rocksdb_statistics_histogram_data_destroy(self.inner);
+        }
+    }
+}
+
+impl HistogramData {
+    fn new() -> HistogramData {
+        unsafe {
+            let data = rocksdb_ffi::rocksdb_statistics_histogram_data_create();
+            assert!(!data.is_null(), "Could not create rocksdb histogram data");
+            HistogramData { inner: data }
+        }
+    }
+
+    pub fn median(&self) -> f64 {
+        unsafe { rocksdb_ffi::rocksdb_statistics_histogram_data_get_median(self.inner) }
+    }
+
+    pub fn percentile95(&self) -> f64 {
+        unsafe { rocksdb_ffi::rocksdb_statistics_histogram_data_get_p95(self.inner) }
+    }
+
+    pub fn percentile99(&self) -> f64 {
+        unsafe { rocksdb_ffi::rocksdb_statistics_histogram_data_get_p99(self.inner) }
+    }
+
+    pub fn average(&self) -> f64 {
+        unsafe { rocksdb_ffi::rocksdb_statistics_histogram_data_get_average(self.inner) }
+    }
+
+    pub fn standard_deviation(&self) -> f64 {
+        unsafe { rocksdb_ffi::rocksdb_statistics_histogram_data_get_std_dev(self.inner) }
+    }
+
+    pub fn max(&self) -> f64 {
+        unsafe { rocksdb_ffi::rocksdb_statistics_histogram_data_get_max(self.inner) }
+    }
+
+    pub fn min(&self) -> f64 {
+        unsafe { rocksdb_ffi::rocksdb_statistics_histogram_data_get_min(self.inner) }
+    }
+
+    /// The number of the recorded values.
+    pub fn count(&self) -> u64 {
+        unsafe { rocksdb_ffi::rocksdb_statistics_histogram_data_get_count(self.inner) }
+    }
+
+    /// The sum of the recorded values.
+    pub fn sum(&self) -> u64 {
+        unsafe { rocksdb_ffi::rocksdb_statistics_histogram_data_get_sum(self.inner) }
+    }
 }
 
 pub struct RateLimiter {
@@ -105,6 +280,41 @@ impl RateLimiter {
             inner: limiter,
         }
     }
+
+    /// Create a rate limiter for all the writes whose rate is dynamically
+    /// adjusted within `[rate_bytes_per_sec / 20, rate_bytes_per_sec]`
+    /// according to demand.
+    pub fn new_auto_tuned(rate_bytes_per_sec: i64,
+                          refill_period_us: i64,
+                          fairness: i32)
+                          -> RateLimiter {
+        let limiter = unsafe {
+            rocksdb_ffi::rocksdb_ratelimiter_create_auto_tuned(rate_bytes_per_sec,
+                                                               refill_period_us,
+                                                               fairness)
+        };
+        RateLimiter { inner: limiter }
+    }
+
+    /// Create a rate limiter that only throttles the IO selected by `mode`.
+    ///
+    /// If `auto_tuned` is true, the rate is dynamically adjusted within
+    /// `[rate_bytes_per_sec / 20, rate_bytes_per_sec]` according to demand.
+    pub fn new_with_mode(rate_bytes_per_sec: i64,
+                         refill_period_us: i64,
+                         fairness: i32,
+                         mode: DBRateLimiterMode,
+                         auto_tuned: bool)
+                         -> RateLimiter {
+        let limiter = unsafe {
+            rocksdb_ffi::rocksdb_ratelimiter_create_with_mode(rate_bytes_per_sec,
+                                                              refill_period_us,
+                                                              fairness,
+                                                              mode,
+                                                              auto_tuned)
+        };
+        RateLimiter { inner: limiter }
+    }
 }
 
 impl Drop for RateLimiter {
@@ -113,6 +323,97 @@ impl Drop for RateLimiter {
     }
 }
 
+/// `Env` is the interface rocksdb uses to access the operating system,
+/// including the thread pools running flushes and compactions.
+///
+/// `Env::default()` wraps the process-wide default env, which is the only
+/// env the C API can create. Its thread pools are shared by every db in
+/// the process, including the ones opened without `Options::set_env`, so
+/// the setters below change them for all of those dbs, not only for the
+/// ones this handle is passed to.
+pub struct Env {
+    inner: *mut DBEnv,
+}
+
+unsafe impl Send for Env {}
+unsafe impl Sync for Env {}
+
+impl Default for Env {
+    /// Return a handle of the process-wide default env.
+    ///
+    /// Every handle returned by it shares the same thread pools, which are
+    /// also used by the dbs opened without `Options::set_env`, so changing
+    /// the number or the priority of the threads through any of them
+    /// changes it for the whole process.
+    fn default() -> Env {
+        let env = unsafe { rocksdb_ffi::rocksdb_create_default_env() };
+        assert!(!env.is_null(), "Could not create rocksdb env");
+        Env { inner: env }
+    }
+}
+
+impl Env {
+    /// Set the number of threads in the low priority pool, which runs
+    /// compactions. The pool is shared by every db in the process.
+    pub fn set_background_threads(&self, n: c_int) {
+        unsafe {
+            rocksdb_ffi::rocksdb_env_set_background_threads(self.inner, n);
+        }
+    }
+
+    pub fn get_background_threads(&self) -> c_int {
+        unsafe { rocksdb_ffi::rocksdb_env_get_background_threads(self.inner) }
+    }
+
+    /// Set the number of threads in the high priority pool, which runs
+    /// flushes. The pool is shared by every db in the process.
+    pub fn set_high_priority_background_threads(&self, n: c_int) {
+        unsafe {
+            rocksdb_ffi::rocksdb_env_set_high_priority_background_threads(self.inner, n);
+        }
+    }
+
+    pub fn get_high_priority_background_threads(&self) -> c_int {
+        unsafe { rocksdb_ffi::rocksdb_env_get_high_priority_background_threads(self.inner) }
+    }
+
+    /// Lower the IO priority of the threads in the low priority pool.
+    /// Only takes effect on linux.
+    pub fn lower_thread_pool_io_priority(&self) {
+        unsafe {
+            rocksdb_ffi::rocksdb_env_lower_thread_pool_io_priority(self.inner);
+        }
+    }
+
+    pub fn lower_high_priority_thread_pool_io_priority(&self) {
+        unsafe {
+            rocksdb_ffi::rocksdb_env_lower_high_priority_thread_pool_io_priority(self.inner);
+        }
+    }
+
+    /// Lower the CPU priority of the threads in the low priority pool.
+    /// Only takes effect on linux.
+    pub fn lower_thread_pool_cpu_priority(&self) {
+        unsafe {
+            rocksdb_ffi::rocksdb_env_lower_thread_pool_cpu_priority(self.inner);
+        }
+    }
+
+    pub fn lower_high_priority_thread_pool_cpu_priority(&self) {
+        unsafe {
+            rocksdb_ffi::rocksdb_env_lower_high_priority_thread_pool_cpu_priority(self.inner);
+        }
+    }
+}
+
+impl Drop for Env {
+    fn drop(&mut self) {
+        unsafe {
+            rocksdb_ffi::rocksdb_env_destroy(self.inner);
+        }
+    }
+}
+
 /// The UnsafeSnap must be destroyed by db, it maybe be leaked
 /// if not using it properly, hence named as unsafe.
 ///
@@ -130,11 +431,24 @@ impl UnsafeSnap {
     pub unsafe fn get_inner(&self) -> *const DBSnapshot {
         self.inner
     }
+
+    /// Return the sequence number of the latest write visible to the
+    /// snapshot.
+    ///
+    /// # Safety
+    ///
+    /// The snapshot must not be released yet, it's read through the raw
+    /// pointer without any check.
+    pub unsafe fn get_sequence_number(&self) -> u64 {
+        rocksdb_ffi::rocksdb_snapshot_get_sequence_number(self.inner)
+    }
 }
 
 pub struct ReadOptions {
     inner: *mut DBReadOptions,
     upper_bound: Vec<u8>,
+    timestamp: Vec<u8>,
+    iter_start_ts: Vec<u8>,
 }
 
 impl Drop for ReadOptions {
@@ -151,6 +465,8 @@ impl Default for ReadOptions {
             ReadOptions {
                 inner: opts,
                 upper_bound: vec![],
+                timestamp: vec![],
+                iter_start_ts: vec![],
             }
         }
     }
@@ -171,12 +487,22 @@ impl ReadOptions {
         }
     }
 
+    /// Whether the data read should be verified against its checksum,
+    /// default is true.
+    pub fn set_verify_checksums(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_readoptions_set_verify_checksums(self.inner, v);
+        }
+    }
+
     pub unsafe fn set_snapshot(&mut self, snapshot: &UnsafeSnap) {
         rocksdb_ffi::rocksdb_readoptions_set_snapshot(self.inner, snapshot.inner);
     }
 
     pub fn set_iterate_upper_bound(&mut self, key: &[u8]) {
-        self.upper_bound = Vec::from(key);
+        // Reuse the buffer of the previous bound if there is any.
+        self.upper_bound.clear();
+        self.upper_bound.extend_from_slice(key);
         unsafe {
             rocksdb_ffi::rocksdb_readoptions_set_iterate_upper_bound(self.inner,
                                                                      self.upper_bound.as_ptr(),
@@ -184,6 +510,148 @@ impl ReadOptions {
         }
     }
 
+    /// Same as `set_iterate_upper_bound`, but `key` is not copied.
+    pub fn set_iterate_upper_bound_static(&mut self, key: &'static [u8]) {
+        self.upper_bound.clear();
+        unsafe {
+            rocksdb_ffi::rocksdb_readoptions_set_iterate_upper_bound(self.inner,
+                                                                     key.as_ptr(),
+                                                                     key.len());
+        }
+    }
+
+    /// Reset all the options to default, including the snapshot, the
+    /// upper bound and the timestamps, so the `ReadOptions` can be reused
+    /// for another request. The fields are reset in place, and the buffers
+    /// are kept for the next setters.
+    ///
+    /// `ReadOptions` can't be shared between threads, keep one instance
+    /// per thread to reuse it.
+    pub fn reset_to_default(&mut self) {
+        self.upper_bound.clear();
+        self.timestamp.clear();
+        self.iter_start_ts.clear();
+        unsafe {
+            let opts = self.inner;
+            rocksdb_ffi::rocksdb_readoptions_set_snapshot(opts, ptr::null());
+            rocksdb_ffi::rocksdb_readoptions_set_iterate_upper_bound(opts, ptr::null(), 0);
+            rocksdb_ffi::rocksdb_readoptions_set_timestamp(opts, ptr::null(), 0);
+            rocksdb_ffi::rocksdb_readoptions_set_iter_start_ts(opts, ptr::null(), 0);
+            rocksdb_ffi::rocksdb_readoptions_set_verify_checksums(opts, true);
+            rocksdb_ffi::rocksdb_readoptions_set_fill_cache(opts, true);
+            rocksdb_ffi::rocksdb_readoptions_set_read_tier(opts, 0);
+            rocksdb_ffi::rocksdb_readoptions_set_tailing(opts, false);
+            rocksdb_ffi::rocksdb_readoptions_set_readahead_size(opts, 0);
+            rocksdb_ffi::rocksdb_readoptions_set_ignore_range_deletions(opts, false);
+            rocksdb_ffi::rocksdb_readoptions_set_pin_data(opts, false);
+            rocksdb_ffi::rocksdb_readoptions_set_auto_prefix_mode(opts, false);
+            rocksdb_ffi::rocksdb_readoptions_set_deadline(opts, 0);
+            rocksdb_ffi::rocksdb_readoptions_set_io_timeout(opts, 0);
+            rocksdb_ffi::rocksdb_readoptions_set_background_purge_on_iterator_cleanup(opts,
+                                                                                    false);
+        }
+    }
+
+    /// Same as `reset_to_default`.
+    pub fn reset(&mut self) {
+        self.reset_to_default()
+    }
+
+    /// Read the versions written at or before `ts`, it's required by the
+    /// column families using `Options::set_comparator_with_u64_ts`, and
+    /// must not be set for the other ones.
+    pub fn set_timestamp(&mut self, ts: u64) {
+        self.timestamp.clear();
+        self.timestamp.extend_from_slice(&comparator::encode_u64_ts(ts));
+        unsafe {
+            rocksdb_ffi::rocksdb_readoptions_set_timestamp(self.inner,
+                                                           self.timestamp.as_ptr(),
+                                                           self.timestamp.len());
+        }
+    }
+
+    /// Make iterators return every version of a key written between `ts`
+    /// and the timestamp set by `set_timestamp`, instead of only the
+    /// newest one.
+    pub fn set_iter_start_ts(&mut self, ts: u64) {
+        self.iter_start_ts.clear();
+        self.iter_start_ts.extend_from_slice(&comparator::encode_u64_ts(ts));
+        unsafe {
+            rocksdb_ffi::rocksdb_readoptions_set_iter_start_ts(self.inner,
+                                                               self.iter_start_ts.as_ptr(),
+                                                               self.iter_start_ts.len());
+        }
+    }
+
+    /// Skip range tombstones when reading, so keys covered by a
+    /// `delete_range` are still visible.
+    pub fn set_ignore_range_deletions(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_readoptions_set_ignore_range_deletions(self.inner, v);
+        }
+    }
+
+    /// Keep the blocks loaded by iterators pinned in memory, so the key
+    /// slices returned by an iterator stay valid as long as the iterator
+    /// is alive, even after it's moved.
+    ///
+    /// `DBIterator::key` still borrows the iterator, it's the caller's
+    /// responsibility to extend the slice lifetime unsafely.
+    pub fn set_pin_data(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_readoptions_set_pin_data(self.inner, v);
+        }
+    }
+
+    /// Use the prefix bloom filters of the column family's prefix extractor
+    /// only when the seek key and the upper bound share the same prefix,
+    /// so the results are the same as a total order seek.
+    ///
+    /// It has no effect if no prefix extractor is configured.
+    pub fn set_auto_prefix_mode(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_readoptions_set_auto_prefix_mode(self.inner, v);
+        }
+    }
+
+    /// Delete the obsolete files pinned by an iterator in a background
+    /// thread instead of when the iterator is dropped.
+    pub fn set_background_purge_on_iterator_cleanup(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_readoptions_set_background_purge_on_iterator_cleanup(self.inner,
+                                                                                    v);
+        }
+    }
+
+    /// Read ahead `size` bytes when iterating, which reduces the number of
+    /// small reads during a large sequential scan.
+    pub fn set_readahead_size(&mut self, size: size_t) {
+        unsafe {
+            rocksdb_ffi::rocksdb_readoptions_set_readahead_size(self.inner, size);
+        }
+    }
+
+    /// Fail a `get` with an "Operation timed out" error once the time
+    /// reaches `micros` microseconds since the unix epoch, 0 means no
+    /// deadline.
+    ///
+    /// It's checked before reading from the files, so a read served from
+    /// the memtables or the block cache never times out.
+    pub fn set_deadline(&mut self, micros: u64) {
+        unsafe {
+            rocksdb_ffi::rocksdb_readoptions_set_deadline(self.inner, micros);
+        }
+    }
+
+    /// Fail a read with an "Operation timed out" error if any single file
+    /// IO of it takes longer than `micros` microseconds, 0 means no
+    /// timeout.
+    pub fn set_io_timeout(&mut self, micros: u64) {
+        unsafe {
+            rocksdb_ffi::rocksdb_readoptions_set_io_timeout(self.inner, micros);
+        }
+    }
+
     pub unsafe fn get_inner(&self) -> *const DBReadOptions {
         self.inner
     }
@@ -230,11 +698,23 @@ impl WriteOptions {
             }
         }
     }
+
+    /// Reset all the options to default in place, so the `WriteOptions`
+    /// can be reused for another request.
+    ///
+    /// `WriteOptions` can't be shared between threads, keep one instance
+    /// per thread to reuse it.
+    pub fn reset_to_default(&mut self) {
+        self.set_sync(false);
+        self.disable_wal(false);
+    }
 }
 
 pub struct Options {
     pub inner: *mut DBOptions,
     filter: Option<CompactionFilterHandle>,
+    env: Option<Arc<Env>>,
+    logger: Option<LoggerHandle>,
 }
 
 impl Drop for Options {
@@ -250,10 +730,7 @@ impl Default for Options {
         unsafe {
             let opts = rocksdb_ffi::rocksdb_options_create();
             assert!(!opts.is_null(), "Could not create rocksdb options");
-            Options {
-                inner: opts,
-                filter: None,
-            }
+            Options::from_raw(opts)
         }
     }
 }
@@ -263,6 +740,15 @@ impl Options {
         Options::default()
     }
 
+    unsafe fn from_raw(inner: *mut DBOptions) -> Options {
+        Options {
+            inner,
+            filter: None,
+            env: None,
+            logger: None,
+        }
+    }
+
     pub fn increase_parallelism(&mut self, parallelism: i32) {
         unsafe {
             rocksdb_ffi::rocksdb_options_increase_parallelism(self.inner, parallelism);
@@ -276,6 +762,16 @@ impl Options {
         }
     }
 
+    /// Tune the options for loading a lot of data at once.
+    ///
+    /// Auto compactions are disabled and all the data is kept in level 0,
+    /// so `DB::compact_range` should be called after the load is done.
+    pub fn prepare_for_bulk_load(&mut self) {
+        unsafe {
+            rocksdb_ffi::rocksdb_options_prepare_for_bulk_load(self.inner);
+        }
+    }
+
     /// Set compaction filter.
     ///
     /// filter will be dropped when this option is dropped or a new filter is
@@ -362,6 +858,26 @@ impl Options {
         }
     }
 
+    /// Use the bytewise comparator with a `u64` user timestamp suffixed to
+    /// every key, the newer versions of a key are ordered first.
+    ///
+    /// A column family opened with it only accepts the `*_with_ts` writes,
+    /// and every read must set a timestamp with `ReadOptions::set_timestamp`,
+    /// mixing them with the plain operations returns an error.
+    pub fn set_comparator_with_u64_ts(&mut self) {
+        unsafe {
+            let cmp = rocksdb_ffi::rocksdb_comparator_with_ts_create(
+                ptr::null_mut(),
+                comparator::u64_ts_destructor_callback,
+                comparator::u64_ts_compare_callback,
+                comparator::u64_ts_compare_ts_callback,
+                comparator::u64_ts_compare_without_ts_callback,
+                comparator::u64_ts_name_callback,
+                comparator::U64_TS_SIZE);
+            rocksdb_ffi::rocksdb_options_set_comparator(self.inner, cmp);
+        }
+    }
+
 
     pub fn set_block_cache_size_mb(&mut self, cache_size: u64) {
         unsafe {
@@ -391,19 +907,96 @@ impl Options {
         }
     }
 
-    pub fn set_disable_data_sync(&mut self, disable: bool) {
+    pub fn get_bytes_per_sync(&self) -> u64 {
+        unsafe { rocksdb_ffi::rocksdb_options_get_bytes_per_sync(self.inner) }
+    }
+
+    /// Same as `set_bytes_per_sync`, but for the WAL files, default is 0,
+    /// which turns it off.
+    pub fn set_wal_bytes_per_sync(&mut self, nbytes: u64) {
         unsafe {
-            if disable {
-                rocksdb_ffi::rocksdb_options_set_disable_data_sync(self.inner, 1);
-            } else {
-                rocksdb_ffi::rocksdb_options_set_disable_data_sync(self.inner, 0);
-            }
+            rocksdb_ffi::rocksdb_options_set_wal_bytes_per_sync(self.inner, nbytes);
+        }
+    }
+
+    pub fn get_wal_bytes_per_sync(&self) -> u64 {
+        unsafe { rocksdb_ffi::rocksdb_options_get_wal_bytes_per_sync(self.inner) }
+    }
+
+    /// If true, the WAL is not flushed after each write but buffered in
+    /// memory until `DB::flush_wal` is called, so the buffered writes are
+    /// lost if the process crashes. Default is false.
+    pub fn set_manual_wal_flush(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_options_set_manual_wal_flush(self.inner, v);
+        }
+    }
+
+    /// Set the maximum number of bytes of the write batches that are
+    /// merged into one group to be written to the WAL together.
+    pub fn set_max_write_batch_group_size_bytes(&mut self, bytes: u64) {
+        unsafe {
+            rocksdb_ffi::rocksdb_options_set_max_write_batch_group_size_bytes(self.inner, bytes);
+        }
+    }
+
+    /// Read sst files with mmap, default is false.
+    ///
+    /// It can't be used together with direct reads.
+    pub fn set_allow_mmap_reads(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_options_set_allow_mmap_reads(self.inner, v);
+        }
+    }
+
+    /// Write sst files with mmap, default is false.
+    ///
+    /// It can't be used together with direct IO for flush and compaction.
+    pub fn set_allow_mmap_writes(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_options_set_allow_mmap_writes(self.inner, v);
+        }
+    }
+
+    /// Read sst files with direct IO, bypassing the OS page cache, default
+    /// is false.
+    ///
+    /// The file system must support `O_DIRECT`, opening the db fails
+    /// otherwise.
+    pub fn set_use_direct_reads(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_options_set_use_direct_reads(self.inner, v);
+        }
+    }
+
+    /// Write sst files in flush and compaction with direct IO, bypassing
+    /// the OS page cache, default is false.
+    pub fn set_use_direct_io_for_flush_and_compaction(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_options_set_use_direct_io_for_flush_and_compaction(self.inner, v);
+        }
+    }
+
+    /// Hint the OS that the access pattern of sst files is random when
+    /// they are opened, default is true.
+    pub fn set_advise_random_on_open(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_options_set_advise_random_on_open(self.inner, v);
+        }
+    }
+
+    /// Don't let child processes inherit the open files, default is true.
+    pub fn set_is_fd_close_on_exec(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_options_set_is_fd_close_on_exec(self.inner, v);
         }
     }
 
-    pub fn allow_os_buffer(&mut self, is_allow: bool) {
+    /// Number of threads used to open the sst files when opening the db,
+    /// default is 16.
+    pub fn set_max_file_opening_threads(&mut self, n: c_int) {
         unsafe {
-            rocksdb_ffi::rocksdb_options_set_allow_os_buffer(self.inner, is_allow);
+            rocksdb_ffi::rocksdb_options_set_max_file_opening_threads(self.inner, n);
         }
     }
 
@@ -439,7 +1032,8 @@ impl Options {
 
     pub fn set_max_bytes_for_level_multiplier(&mut self, mul: i32) {
         unsafe {
-            rocksdb_ffi::rocksdb_options_set_max_bytes_for_level_multiplier(self.inner, mul);
+            rocksdb_ffi::rocksdb_options_set_max_bytes_for_level_multiplier(self.inner,
+                                                                           mul as f64);
         }
     }
 
@@ -497,12 +1091,6 @@ impl Options {
         }
     }
 
-    pub fn set_filter_deletes(&mut self, filter: bool) {
-        unsafe {
-            rocksdb_ffi::rocksdb_options_set_filter_deletes(self.inner, filter);
-        }
-    }
-
     pub fn set_disable_auto_compactions(&mut self, disable: bool) {
         unsafe {
             if disable {
@@ -519,6 +1107,53 @@ impl Options {
         }
     }
 
+    /// If true, obsolete files are not deleted by the thread dropping the
+    /// last reference to them, e.g. an iterator or a released column
+    /// family, but scheduled to be deleted by a background thread.
+    /// Default is false.
+    pub fn set_avoid_unnecessary_blocking_io(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_options_set_avoid_unnecessary_blocking_io(self.inner, v);
+        }
+    }
+
+    /// If true, the sizes of the sst files are not checked against the
+    /// MANIFEST when opening the db, which speeds up opening a db with a
+    /// huge number of files. Default is false.
+    pub fn set_skip_checking_sst_file_sizes_on_db_open(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_options_set_skip_checking_sst_file_sizes_on_db_open(self.inner,
+                                                                                   v);
+        }
+    }
+
+    /// If true, the db fails fast on any corruption it detects, e.g. when
+    /// opening it or reading the WAL, instead of trying to carry on.
+    /// Default is true.
+    pub fn set_paranoid_checks(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_options_set_paranoid_checks(self.inner, v);
+        }
+    }
+
+    /// If true, the stats of the sst files are not loaded when opening the
+    /// db, which saves reading one block of each file. Default is false.
+    pub fn set_skip_stats_update_on_db_open(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_options_set_skip_stats_update_on_db_open(self.inner, v);
+        }
+    }
+
+    /// Reserve the bottommost level for files ingested with
+    /// `IngestExternalFileOptions::set_ingest_behind`, compaction never
+    /// outputs to that level. Ingesting behind fails if the db is not
+    /// opened with this option. Default is false.
+    pub fn set_allow_ingest_behind(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_options_set_allow_ingest_behind(self.inner, v);
+        }
+    }
+
     pub fn set_report_bg_io_stats(&mut self, enable: bool) {
         unsafe {
             if enable {
@@ -535,6 +1170,21 @@ impl Options {
         }
     }
 
+    /// If true, the WALs are tracked in the MANIFEST, and opening the db
+    /// fails if a synced WAL is missing or shorter than recorded there,
+    /// whatever the `set_wal_recovery_mode`. Default is false.
+    pub fn set_track_and_verify_wals_in_manifest(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_options_set_track_and_verify_wals_in_manifest(self.inner, v);
+        }
+    }
+
+    pub fn get_track_and_verify_wals_in_manifest(&self) -> bool {
+        unsafe {
+            rocksdb_ffi::rocksdb_options_get_track_and_verify_wals_in_manifest(self.inner) != 0
+        }
+    }
+
     pub fn enable_statistics(&mut self) {
         unsafe {
             rocksdb_ffi::rocksdb_options_enable_statistics(self.inner);
@@ -557,18 +1207,121 @@ impl Options {
         }
     }
 
+    /// Return the histogram `hist_type` of the statistics, every value of
+    /// it is 0 if the statistics are not enabled.
+    pub fn get_statistics_histogram(&self, hist_type: DBStatisticsHistogramType) -> HistogramData {
+        let data = HistogramData::new();
+        unsafe {
+            rocksdb_ffi::rocksdb_options_statistics_get_histogram_data(self.inner,
+                                                                       hist_type,
+                                                                       data.inner);
+        }
+        data
+    }
+
+    /// Return the line of the histogram `hist_type` in `get_statistics`,
+    /// which has its percentiles, count and sum.
+    pub fn get_statistics_histogram_string(&self,
+                                           hist_type: DBStatisticsHistogramType)
+                                           -> Option<String> {
+        let stats = self.get_statistics()?;
+        let prefix = format!("{} ", hist_type.name());
+        stats.lines().find(|l| l.starts_with(&prefix)).map(|l| l.to_owned())
+    }
+
+    /// Return the count of the ticker `ticker` in `get_statistics`, or
+    /// `None` if the statistics are not enabled.
+    pub fn get_statistics_ticker_count(&self, ticker: DBStatisticsTickerType) -> Option<u64> {
+        let stats = self.get_statistics()?;
+        let prefix = format!("{} COUNT : ", ticker.name());
+        stats.lines().find(|l| l.starts_with(&prefix)).and_then(|l| l[prefix.len()..].parse().ok())
+    }
+
     pub fn set_stats_dump_period_sec(&mut self, period: usize) {
         unsafe {
             rocksdb_ffi::rocksdb_options_set_stats_dump_period_sec(self.inner, period);
         }
     }
 
+    /// Snapshot the statistics into the in-memory stats history every
+    /// `period` seconds, 0 disables it. Default is 600.
+    pub fn set_stats_persist_period_sec(&mut self, period: u32) {
+        unsafe {
+            rocksdb_ffi::rocksdb_options_set_stats_persist_period_sec(self.inner, period);
+        }
+    }
+
+    pub fn get_stats_persist_period_sec(&self) -> u32 {
+        unsafe { rocksdb_ffi::rocksdb_options_get_stats_persist_period_sec(self.inner) }
+    }
+
     pub fn set_num_levels(&mut self, n: c_int) {
         unsafe {
             rocksdb_ffi::rocksdb_options_set_num_levels(self.inner, n);
         }
     }
 
+    /// Only log lines at `level` or above are written to the info log.
+    pub fn set_info_log_level(&mut self, level: DBInfoLogLevel) {
+        unsafe {
+            rocksdb_ffi::rocksdb_options_set_info_log_level(self.inner, level);
+        }
+    }
+
+    /// Route the info log to `logger` instead of the LOG file in the db
+    /// directory. Only lines at `level` or above are passed to it.
+    ///
+    /// logger will be dropped when this option is dropped or a new logger
+    /// is set.
+    pub fn set_logger(&mut self, level: DBInfoLogLevel, logger: Box<dyn Logger>) {
+        let handle = new_logger(level, logger);
+        unsafe {
+            rocksdb_ffi::rocksdb_options_set_info_log(self.inner, handle.inner);
+        }
+        self.logger = Some(handle);
+    }
+
+    /// Put the WAL files in `path` instead of the db directory.
+    pub fn set_wal_dir(&mut self, path: &str) {
+        let cpath = CString::new(path.as_bytes()).unwrap();
+        unsafe {
+            rocksdb_ffi::rocksdb_options_set_wal_dir(self.inner, cpath.as_ptr());
+        }
+    }
+
+    /// Set the directories sst files are put in, along with the target size
+    /// of each directory.
+    ///
+    /// Newer data is placed in earlier paths, and older data is moved to
+    /// later paths once the target size is exceeded.
+    pub fn set_db_paths(&mut self, paths: &[(&str, u64)]) {
+        let cpaths: Vec<CString> = paths.iter()
+            .map(|&(p, _)| CString::new(p.as_bytes()).unwrap())
+            .collect();
+        let dbpaths: Vec<_> = cpaths.iter()
+            .zip(paths)
+            .map(|(p, &(_, size))| unsafe { rocksdb_ffi::rocksdb_dbpath_create(p.as_ptr(), size) })
+            .collect();
+        unsafe {
+            rocksdb_ffi::rocksdb_options_set_db_paths(self.inner,
+                                                      dbpaths.as_ptr() as *const *const _,
+                                                      dbpaths.len() as size_t);
+            // The paths are copied into options.
+            for p in dbpaths {
+                rocksdb_ffi::rocksdb_dbpath_destroy(p);
+            }
+        }
+    }
+
+    /// Use `env` to access the operating system, the env is kept alive as
+    /// long as this option.
+    pub fn set_env(&mut self, env: Arc<Env>) {
+        unsafe {
+            rocksdb_ffi::rocksdb_options_set_env(self.inner, env.inner);
+        }
+        self.env = Some(env);
+    }
+
     pub fn set_ratelimiter(&mut self, rate_bytes_per_sec: i64) {
         let rate_limiter = RateLimiter::new(rate_bytes_per_sec,
                                             100 * 1000 /* 100ms should work for most cases */,
@@ -577,6 +1330,14 @@ impl Options {
             rocksdb_ffi::rocksdb_options_set_ratelimiter(self.inner, rate_limiter.inner);
         }
     }
+
+    /// Share `limiter` with this option, it can be dropped once this
+    /// function returns.
+    pub fn set_rate_limiter(&mut self, limiter: &RateLimiter) {
+        unsafe {
+            rocksdb_ffi::rocksdb_options_set_ratelimiter(self.inner, limiter.inner);
+        }
+    }
 }
 
 pub struct FlushOptions {
@@ -603,6 +1364,153 @@ impl Drop for FlushOptions {
     }
 }
 
+/// Load the options in the latest OPTIONS file of the db at `path`.
+///
+/// Return the db options and the options of each column family, which can
+/// be passed to `DB::open_cf` directly. Comparators, merge operators and
+/// compaction filters are not persisted in the OPTIONS file, they need to
+/// be set again. Block based tables use a new 8MB block cache.
+///
+/// If `ignore_unknown_options` is true, options written by a newer version
+/// of rocksdb are ignored instead of failing.
+pub fn load_latest_options(path: &str,
+                           env: &Env,
+                           ignore_unknown_options: bool)
+                           -> Result<(Options, Vec<(String, Options)>), String> {
+    let cpath = match CString::new(path.as_bytes()) {
+        Ok(c) => c,
+        Err(_) => return Err("Failed to convert path to CString when loading options".to_owned()),
+    };
+    let mut db_opt = ptr::null_mut();
+    let mut num_cfs = 0;
+    let mut cf_names = ptr::null_mut();
+    let mut cf_opts = ptr::null_mut();
+    unsafe {
+        let cache = rocksdb_ffi::new_cache(8 << 20);
+        let mut err = ptr::null_mut();
+        rocksdb_ffi::rocksdb_load_latest_options(cpath.as_ptr(),
+                                                 env.inner,
+                                                 ignore_unknown_options,
+                                                 cache,
+                                                 &mut db_opt,
+                                                 &mut num_cfs,
+                                                 &mut cf_names,
+                                                 &mut cf_opts,
+                                                 &mut err);
+        // The loaded options hold their own reference to the cache.
+        rocksdb_ffi::rocksdb_cache_destroy(cache);
+        if !err.is_null() {
+            return Err(rocksdb_ffi::error_message(err));
+        }
+
+        let names = slice::from_raw_parts(cf_names, num_cfs);
+        let opts = slice::from_raw_parts(cf_opts, num_cfs);
+        let mut cfs = Vec::with_capacity(num_cfs);
+        for (&c_name, &opt) in names.iter().zip(opts) {
+            let name = CStr::from_ptr(c_name).to_string_lossy().into_owned();
+            cfs.push((name, Options::from_raw(opt)));
+            libc::free(c_name as *mut c_void);
+        }
+        libc::free(cf_names as *mut c_void);
+        libc::free(cf_opts as *mut c_void);
+        Ok((Options::from_raw(db_opt), cfs))
+    }
+}
+
+pub struct EnvOptions {
+    pub inner: *mut DBEnvOptions,
+}
+
+impl Default for EnvOptions {
+    fn default() -> EnvOptions {
+        unsafe { EnvOptions { inner: rocksdb_ffi::rocksdb_envoptions_create() } }
+    }
+}
+
+impl EnvOptions {
+    pub fn new() -> EnvOptions {
+        EnvOptions::default()
+    }
+}
+
+impl Drop for EnvOptions {
+    fn drop(&mut self) {
+        unsafe {
+            rocksdb_ffi::rocksdb_envoptions_destroy(self.inner);
+        }
+    }
+}
+
+pub struct IngestExternalFileOptions {
+    pub inner: *mut DBIngestExternalFileOptions,
+}
+
+impl Default for IngestExternalFileOptions {
+    fn default() -> IngestExternalFileOptions {
+        unsafe {
+            IngestExternalFileOptions {
+                inner: rocksdb_ffi::rocksdb_ingestexternalfileoptions_create(),
+            }
+        }
+    }
+}
+
+impl IngestExternalFileOptions {
+    pub fn new() -> IngestExternalFileOptions {
+        IngestExternalFileOptions::default()
+    }
+
+    /// If true, the files are moved instead of copied, default is false.
+    pub fn set_move_files(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_ingestexternalfileoptions_set_move_files(self.inner, v);
+        }
+    }
+
+    /// If false, snapshots taken before the ingestion may see the ingested
+    /// keys, default is true.
+    pub fn set_snapshot_consistency(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_ingestexternalfileoptions_set_snapshot_consistency(self.inner, v);
+        }
+    }
+
+    /// If false, the ingestion fails if the files overlap with existing
+    /// keys, default is true.
+    pub fn set_allow_global_seqno(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_ingestexternalfileoptions_set_allow_global_seqno(self.inner, v);
+        }
+    }
+
+    /// If false, the ingestion fails if the files overlap with the
+    /// memtable instead of flushing it, default is true.
+    pub fn set_allow_blocking_flush(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_ingestexternalfileoptions_set_allow_blocking_flush(self.inner, v);
+        }
+    }
+
+    /// If true, the files are ingested into the bottommost level, below all
+    /// the existing data, so existing keys are not overwritten. Default is
+    /// false.
+    ///
+    /// It requires the db to be opened with `allow_ingest_behind`.
+    pub fn set_ingest_behind(&mut self, v: bool) {
+        unsafe {
+            rocksdb_ffi::rocksdb_ingestexternalfileoptions_set_ingest_behind(self.inner, v);
+        }
+    }
+}
+
+impl Drop for IngestExternalFileOptions {
+    fn drop(&mut self) {
+        unsafe {
+            rocksdb_ffi::rocksdb_ingestexternalfileoptions_destroy(self.inner);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Options;
@@ -619,6 +1527,7 @@ mod tests {
         let mut opts = Options::new();
         opts.enable_statistics();
         opts.set_stats_dump_period_sec(60);
+        opts.set_stats_persist_period_sec(1);
         assert!(opts.get_statistics().is_some());
 
         let opts = Options::new();
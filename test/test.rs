@@ -1,9 +1,13 @@
 extern crate rocksdb;
 extern crate tempdir;
 
+mod util;
+
 mod test_iterator;
 mod test_multithreaded;
 mod test_column_family;
 mod test_compaction_filter;
 mod test_compact_range;
 mod test_rocksdb_options;
+mod test_ingest_external_file;
+mod test_user_timestamp;
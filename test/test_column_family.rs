@@ -13,8 +13,13 @@
 // limitations under the License.
 //
 
-use rocksdb::{DB, MergeOperands, Options, Writable};
+use rocksdb::{DB, MergeOperands, Options, SeekKey, Writable};
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
 use tempdir::TempDir;
+use util::sst_files;
 
 #[test]
 pub fn test_column_family() {
@@ -123,6 +128,166 @@ pub fn test_column_family() {
     }
 }
 
+#[test]
+fn test_cf_handle() {
+    let path = TempDir::new("_rust_rocksdb_cf_handle").expect("");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    let mut db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    assert!(db.cf_handle("default").is_some());
+    assert!(db.cf_handle("cf1").is_none());
+
+    for name in &["cf1", "cf2", "cf3"] {
+        db.create_cf(name, &Options::new()).unwrap();
+    }
+    for name in &["cf1", "cf2", "cf3"] {
+        let cf = db.cf_handle(name).unwrap();
+        db.put_cf(cf, b"k", name.as_bytes()).unwrap();
+    }
+    for name in &["cf1", "cf2", "cf3"] {
+        let cf = db.cf_handle(name).unwrap();
+        assert_eq!(db.get_cf(cf, b"k").unwrap().unwrap().to_utf8().unwrap(), *name);
+    }
+    assert!(db.cf_handle("default").is_some());
+    assert!(db.get(b"k").unwrap().is_none());
+}
+
+#[test]
+fn test_create_cf_at_runtime() {
+    let path = TempDir::new("_rust_rocksdb_create_cf_at_runtime").expect("");
+    let path_str = path.path().to_str().unwrap();
+    {
+        let mut opts = Options::new();
+        opts.create_if_missing(true);
+        let mut db = DB::open(opts, path_str).unwrap();
+        let mut cf_opts = Options::new();
+        cf_opts.add_merge_operator("test operator", test_provided_merge);
+        db.create_cf("cf1", &cf_opts).unwrap();
+
+        // Creating an existing column family fails and keeps the old one.
+        assert!(db.create_cf("cf1", &Options::new()).is_err());
+        assert!(db.create_cf("default", &Options::new()).is_err());
+        assert_eq!(db.cf_names(), vec!["cf1", "default"]);
+
+        let cf1 = db.cf_handle("cf1").unwrap();
+        db.put_cf(cf1, b"k1", b"a").unwrap();
+        db.merge_cf(cf1, b"k1", b"b").unwrap();
+        db.merge_cf(cf1, b"k1", b"c").unwrap();
+        assert_eq!(db.get_cf(cf1, b"k1").unwrap().unwrap().to_utf8().unwrap(),
+                   "abc");
+        assert!(db.get(b"k1").unwrap().is_none());
+    }
+
+    let mut cfs = DB::list_column_families(&Options::new(), path_str).unwrap();
+    cfs.sort();
+    assert_eq!(cfs, vec!["cf1", "default"]);
+
+    {
+        let mut cf_opts = Options::new();
+        cf_opts.add_merge_operator("test operator", test_provided_merge);
+        let mut db = DB::open_cf(Options::new(), path_str, &["cf1"], &[&cf_opts]).unwrap();
+        {
+            let cf1 = db.cf_handle("cf1").unwrap();
+            assert_eq!(db.get_cf(cf1, b"k1").unwrap().unwrap().to_utf8().unwrap(),
+                       "abc");
+        }
+        db.drop_cf("cf1").unwrap();
+        assert!(db.cf_handle("cf1").is_none());
+        assert_eq!(db.cf_names(), vec!["default"]);
+    }
+
+    let cfs = DB::list_column_families(&Options::new(), path_str).unwrap();
+    assert_eq!(cfs, vec!["default"]);
+}
+
+#[test]
+fn test_owned_cf_handle_outlives_drop_cf() {
+    let path = TempDir::new("_rust_rocksdb_owned_cf_handle").expect("");
+    let path_str = path.path().to_str().unwrap();
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    let mut db = DB::open(opts, path_str).unwrap();
+    db.create_cf("cf1", &Options::new()).unwrap();
+    let cf1 = db.cf_handle_owned("cf1").unwrap();
+    db.put_cf(&cf1, b"k1", b"v1").unwrap();
+    db.compact_range_cf(&cf1, None, None);
+    let cf1_files = sst_files(path.path());
+    assert!(!cf1_files.is_empty());
+
+    db.drop_cf("cf1").unwrap();
+    assert!(db.cf_handle("cf1").is_none());
+
+    let db = Arc::new(db);
+    let db2 = db.clone();
+    let cf1_2 = cf1.clone();
+    thread::spawn(move || {
+            // The dropped column family is still readable through the handle.
+            assert_eq!(db2.get_cf(&cf1_2, b"k1").unwrap().unwrap().to_utf8().unwrap(),
+                       "v1");
+        })
+        .join()
+        .unwrap();
+    assert!(cf1_files.iter().all(|f| f.exists()));
+
+    drop(cf1);
+    // Flush triggers purging obsolete files.
+    db.put(b"k2", b"v2").unwrap();
+    db.flush(true).unwrap();
+    assert!(cf1_files.iter().all(|f| !f.exists()));
+}
+
+#[test]
+fn test_drop_cf_while_reading() {
+    let path = TempDir::new("_rust_rocksdb_drop_cf_while_reading").expect("");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    let mut db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    db.create_cf("cf1", &Options::new()).unwrap();
+    let cf1 = db.cf_handle_owned("cf1").unwrap();
+    for i in 0..100 {
+        db.put_cf(&cf1, format!("k{:02}", i).as_bytes(), b"v").unwrap();
+    }
+
+    let db = Arc::new(RwLock::new(db));
+    let dropped = Arc::new(AtomicBool::new(false));
+    let (db2, dropped2) = (db.clone(), dropped.clone());
+    let reader = thread::spawn(move || {
+        let mut reads_after_drop = 0;
+        while reads_after_drop < 1000 {
+            let is_dropped = dropped2.load(Ordering::SeqCst);
+            {
+                let db = db2.read().unwrap();
+                let mut iter = db.iter_cf(&cf1);
+                iter.seek(SeekKey::Start);
+                assert_eq!(iter.count(), 100);
+                assert!(db.get_cf(&cf1, b"k42").unwrap().is_some());
+            }
+            if is_dropped {
+                reads_after_drop += 1;
+            }
+        }
+    });
+
+    thread::sleep(Duration::from_millis(10));
+    db.write().unwrap().drop_cf("cf1").unwrap();
+    dropped.store(true, Ordering::SeqCst);
+    reader.join().unwrap();
+    assert!(db.read().unwrap().cf_handle("cf1").is_none());
+}
+
+#[test]
+fn test_owned_cf_handle_outlives_db() {
+    let path = TempDir::new("_rust_rocksdb_owned_cf_handle_outlives_db").expect("");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    let mut db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    db.create_cf("cf1", &Options::new()).unwrap();
+    let cf1 = db.cf_handle_owned("cf1").unwrap();
+    drop(db);
+    // Must not touch the closed db.
+    drop(cf1);
+}
+
 fn test_provided_merge(_: &[u8],
                        existing_val: Option<&[u8]>,
                        operands: &mut MergeOperands)
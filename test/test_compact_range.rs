@@ -1,5 +1,6 @@
 
-use rocksdb::{DB, Options, Range, Writable};use tempdir::TempDir;
+use rocksdb::{DB, Options, Range, Writable};use std::time::Duration;
+use tempdir::TempDir;
 
 
 #[test]
@@ -22,13 +23,102 @@ fn test_compact_range() {
 
     // flush memtable to sst file
     db.flush(true).unwrap();
-    let old_size = db.get_approximate_sizes(&[Range::new(b"k0", b"k6")])[0];
+    let old_size = db.get_approximate_sizes(&[Range::new(b"k0", b"k6")]).unwrap()[0];
 
     // delete all and compact whole range
     for &(ref k, _) in &samples {
         db.delete(k).unwrap()
     }
     db.compact_range(None, None);
-    let new_size = db.get_approximate_sizes(&[Range::new(b"k0", b"k6")])[0];
+    let new_size = db.get_approximate_sizes(&[Range::new(b"k0", b"k6")]).unwrap()[0];
     assert!(old_size > new_size);
 }
+
+#[test]
+fn test_compact_range_cf() {
+    let path = TempDir::new("_rust_rocksdb_test_compact_range_cf").expect("");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    let mut db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    let mut cf_opts = Options::new();
+    cf_opts.set_num_levels(2);
+    db.create_cf("cf1", &cf_opts).unwrap();
+    let cf1 = db.cf_handle("cf1").unwrap();
+    let default = db.cf_handle("default").unwrap();
+    for i in 0..5 {
+        let k = format!("k{}", i);
+        db.put_cf(cf1, k.as_bytes(), b"v").unwrap();
+        db.put(k.as_bytes(), b"v").unwrap();
+    }
+    db.flush(true).unwrap();
+    assert_eq!(db.get_property_int_cf(cf1, "rocksdb.num-files-at-level1"), Some(0));
+    assert_eq!(db.get_property_int_cf(default, "rocksdb.num-files-at-level0"),
+               Some(1));
+
+    // Only cf1 is flushed and compacted to the bottommost level.
+    db.compact_range_cf(cf1, Some(b"k0"), Some(b"k5"));
+    assert_eq!(db.get_property_int_cf(cf1, "rocksdb.num-files-at-level0"), Some(0));
+    assert_eq!(db.get_property_int_cf(cf1, "rocksdb.num-files-at-level1"), Some(1));
+    assert_eq!(db.get_property_int_cf(default, "rocksdb.num-files-at-level0"),
+               Some(1));
+    for i in 0..5 {
+        assert!(db.get_cf(cf1, format!("k{}", i).as_bytes()).unwrap().is_some());
+    }
+}
+
+#[test]
+fn test_compact_range_and_wait() {
+    let path = TempDir::new("_rust_rocksdb_test_compact_range_and_wait").expect("");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.set_level_zero_file_num_compaction_trigger(2);
+    opts.set_target_file_size_base(64 * 1024);
+    opts.set_max_bytes_for_level_base(256 * 1024);
+    let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    let value = vec![b'v'; 1024];
+    for round in 0..8 {
+        for i in 0..500 {
+            let k = format!("k{:04}-{}", i, round);
+            db.put(k.as_bytes(), &value).unwrap();
+        }
+        db.flush(true).unwrap();
+    }
+
+    // Compacting a part of the range pushes data down to L1, which is over
+    // its target size and needs further automatic compactions.
+    db.compact_range_and_wait(Some(b"k0000"), Some(b"k0250"), Duration::from_secs(60))
+        .unwrap();
+    assert_eq!(db.get_property_int("rocksdb.compaction-pending"), Some(0));
+    assert_eq!(db.get_property_int("rocksdb.num-running-compactions"), Some(0));
+}
+
+#[test]
+fn test_disable_manual_compaction() {
+    let path = TempDir::new("_rust_rocksdb_test_disable_manual_compaction").expect("");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.set_disable_auto_compactions(true);
+    let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    for round in 0..4 {
+        for i in 0..100 {
+            db.put(format!("k{:03}", i).as_bytes(), format!("v{}", round).as_bytes()).unwrap();
+        }
+        db.flush(true).unwrap();
+    }
+    let l0_files = || db.get_property_int("rocksdb.num-files-at-level0").unwrap();
+    assert_eq!(l0_files(), 4);
+
+    // Paused manual compactions return early without compacting anything.
+    db.disable_manual_compaction();
+    db.disable_manual_compaction();
+    db.compact_range(None, None);
+    assert_eq!(l0_files(), 4);
+    db.enable_manual_compaction();
+    db.compact_range(None, None);
+    assert_eq!(l0_files(), 4);
+
+    db.enable_manual_compaction();
+    db.compact_range(None, None);
+    assert_eq!(l0_files(), 0);
+    assert_eq!(&*db.get(b"k042").unwrap().unwrap(), b"v3");
+}
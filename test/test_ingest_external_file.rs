@@ -0,0 +1,131 @@
+use rocksdb::*;
+use std::cmp::Ordering;
+use tempdir::TempDir;
+
+fn gen_sst(opt: Options, path: &str, data: &[(&[u8], &[u8])]) {
+    let mut writer = SstFileWriter::new(EnvOptions::new(), opt);
+    writer.open(path).unwrap();
+    for &(k, v) in data {
+        writer.put(k, v).unwrap();
+    }
+    assert!(writer.file_size() > 0);
+    writer.finish().unwrap();
+}
+
+#[test]
+fn test_ingest_external_file() {
+    let path = TempDir::new("_rust_rocksdb_ingest_sst").expect("");
+    let db = DB::open_default(path.path().join("db").to_str().unwrap()).unwrap();
+    let gen_path = TempDir::new("_rust_rocksdb_ingest_sst_gen").expect("");
+    let test_sstfile = gen_path.path().join("test_sst_file");
+    let test_sstfile_str = test_sstfile.to_str().unwrap();
+
+    gen_sst(Options::new(),
+            test_sstfile_str,
+            &[(b"k1", b"v1"), (b"k2", b"v2")]);
+    let ingest_opt = IngestExternalFileOptions::new();
+    db.ingest_external_file(&ingest_opt, &[test_sstfile_str]).unwrap();
+    assert!(test_sstfile.exists());
+    assert_eq!(db.get(b"k1").unwrap().unwrap(), &b"v1"[..]);
+    assert_eq!(db.get(b"k2").unwrap().unwrap(), &b"v2"[..]);
+
+    gen_sst(Options::new(),
+            test_sstfile_str,
+            &[(b"k1", b"v3"), (b"k2", b"v4")]);
+    let mut ingest_opt = IngestExternalFileOptions::new();
+    ingest_opt.set_move_files(true);
+    db.ingest_external_file(&ingest_opt, &[test_sstfile_str]).unwrap();
+    assert!(!test_sstfile.exists());
+    assert_eq!(db.get(b"k1").unwrap().unwrap(), &b"v3"[..]);
+    assert_eq!(db.get(b"k2").unwrap().unwrap(), &b"v4"[..]);
+}
+
+#[test]
+fn test_ingest_behind() {
+    let path = TempDir::new("_rust_rocksdb_ingest_behind").expect("");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.set_allow_ingest_behind(true);
+    let db = DB::open(opts, path.path().join("db").to_str().unwrap()).unwrap();
+    db.put(b"k1", b"new").unwrap();
+    db.flush(true).unwrap();
+    db.put(b"k3", b"new").unwrap();
+
+    let gen_path = TempDir::new("_rust_rocksdb_ingest_behind_gen").expect("");
+    let test_sstfile = gen_path.path().join("test_sst_file");
+    let test_sstfile_str = test_sstfile.to_str().unwrap();
+    gen_sst(Options::new(),
+            test_sstfile_str,
+            &[(b"k1", b"old"), (b"k2", b"old"), (b"k3", b"old")]);
+    let mut ingest_opt = IngestExternalFileOptions::new();
+    ingest_opt.set_ingest_behind(true);
+    db.ingest_external_file(&ingest_opt, &[test_sstfile_str]).unwrap();
+
+    // Newer keys in the db win over the ingested ones.
+    assert_eq!(db.get(b"k1").unwrap().unwrap(), &b"new"[..]);
+    assert_eq!(db.get(b"k2").unwrap().unwrap(), &b"old"[..]);
+    assert_eq!(db.get(b"k3").unwrap().unwrap(), &b"new"[..]);
+    db.compact_range(None, None);
+    assert_eq!(db.get(b"k1").unwrap().unwrap(), &b"new"[..]);
+    assert_eq!(db.get(b"k2").unwrap().unwrap(), &b"old"[..]);
+}
+
+#[test]
+fn test_ingest_behind_requires_allow_ingest_behind() {
+    let path = TempDir::new("_rust_rocksdb_ingest_behind_not_allowed").expect("");
+    let db = DB::open_default(path.path().join("db").to_str().unwrap()).unwrap();
+    let gen_path = TempDir::new("_rust_rocksdb_ingest_behind_not_allowed_gen").expect("");
+    let test_sstfile = gen_path.path().join("test_sst_file");
+    let test_sstfile_str = test_sstfile.to_str().unwrap();
+    gen_sst(Options::new(), test_sstfile_str, &[(b"k1", b"v1")]);
+
+    let mut ingest_opt = IngestExternalFileOptions::new();
+    ingest_opt.set_ingest_behind(true);
+    assert!(db.ingest_external_file(&ingest_opt, &[test_sstfile_str]).is_err());
+    assert!(db.get(b"k1").unwrap().is_none());
+
+    ingest_opt.set_ingest_behind(false);
+    db.ingest_external_file(&ingest_opt, &[test_sstfile_str]).unwrap();
+    assert_eq!(db.get(b"k1").unwrap().unwrap(), &b"v1"[..]);
+}
+
+fn reverse_compare(a: &[u8], b: &[u8]) -> i32 {
+    match b.cmp(a) {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    }
+}
+
+fn reverse_cf_options() -> Options {
+    let mut opts = Options::new();
+    opts.add_comparator("test reverse comparator", reverse_compare);
+    opts
+}
+
+#[test]
+fn test_ingest_with_cf_comparator() {
+    let path = TempDir::new("_rust_rocksdb_ingest_with_cf_comparator").expect("");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    let mut db = DB::open(opts, path.path().join("db").to_str().unwrap()).unwrap();
+    db.create_cf("reverse", &reverse_cf_options()).unwrap();
+    let gen_path = TempDir::new("_rust_rocksdb_ingest_with_cf_comparator_gen").expect("");
+    let test_sstfile = gen_path.path().join("test_sst_file");
+    let test_sstfile_str = test_sstfile.to_str().unwrap();
+
+    // Keys are added in the order of the reverse comparator.
+    gen_sst(reverse_cf_options(),
+            test_sstfile_str,
+            &[(b"k3", b"v3"), (b"k2", b"v2"), (b"k1", b"v1")]);
+    let ingest_opt = IngestExternalFileOptions::new();
+    assert!(db.ingest_external_file(&ingest_opt, &[test_sstfile_str]).is_err());
+
+    let cf = db.cf_handle("reverse").unwrap();
+    db.ingest_external_file_cf(cf, &ingest_opt, &[test_sstfile_str]).unwrap();
+    let mut iter = db.iter_cf(cf);
+    iter.seek(SeekKey::Start);
+    let keys: Vec<_> = iter.map(|(k, _)| k).collect();
+    assert_eq!(keys, vec![b"k3".to_vec(), b"k2".to_vec(), b"k1".to_vec()]);
+    assert_eq!(db.get_cf(cf, b"k2").unwrap().unwrap(), &b"v2"[..]);
+}
@@ -1,5 +1,11 @@
 use rocksdb::*;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::slice;
+use std::thread;
+use std::time::{Duration, Instant};
 use tempdir::TempDir;
+use util::sst_files;
 
 fn prev_collect<'a>(iter: &mut DBIterator<'a>) -> Vec<Kv> {
     let mut buf = vec![];
@@ -138,3 +144,403 @@ fn read_with_upper_bound() {
         assert_eq!(count, 2);
     }
 }
+
+#[test]
+fn read_with_static_upper_bound_and_reset() {
+    let path = TempDir::new("_rust_rocksdb_read_with_static_upper_bound").expect("");
+    let db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+    db.put(b"k1-0", b"a").unwrap();
+    db.put(b"k1-1", b"b").unwrap();
+    db.put(b"k2-0", b"c").unwrap();
+
+    let scan = |readopts: ReadOptions| {
+        let mut iter = db.iter_opt(readopts);
+        iter.seek(SeekKey::Start);
+        iter.collect::<Vec<_>>()
+    };
+
+    let mut owned = ReadOptions::new();
+    owned.set_iterate_upper_bound(b"k2");
+    let mut borrowed = ReadOptions::new();
+    borrowed.set_iterate_upper_bound_static(b"k2");
+    let expected = scan(owned);
+    assert_eq!(expected.len(), 2);
+    assert_eq!(scan(borrowed), expected);
+
+    let mut readopts = ReadOptions::new();
+    readopts.set_iterate_upper_bound(b"k1-1");
+    readopts.reset_to_default();
+    assert_eq!(scan(readopts).len(), 3);
+
+    let mut readopts = ReadOptions::new();
+    readopts.set_iterate_upper_bound_static(b"k1-1");
+    readopts.reset_to_default();
+    readopts.set_iterate_upper_bound(b"k2");
+    assert_eq!(scan(readopts), expected);
+}
+
+#[test]
+fn test_reverse_iterator() {
+    let path = TempDir::new("_rust_rocksdb_reverse_iterator_test").expect("");
+    let db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+    let mut expected = vec![];
+    for i in 0..10 {
+        let k = format!("k{}", i).into_bytes();
+        let v = format!("v{}", i).into_bytes();
+        db.put(&k, &v).unwrap();
+        expected.push((k, v));
+    }
+    expected.reverse();
+
+    let mut iter = db.iter();
+    iter.seek(SeekKey::End);
+    assert_eq!(iter.reverse().collect::<Vec<_>>(), expected);
+    assert!(!iter.valid());
+
+    // prev at the first key makes the iterator invalid.
+    iter.seek(SeekKey::Start);
+    assert!(!iter.prev());
+    assert!(!iter.valid());
+
+    iter.seek(SeekKey::Key(b"k5"));
+    assert_eq!(iter.reverse().collect::<Vec<_>>(), &expected[4..]);
+}
+
+#[test]
+fn test_iterator_status() {
+    let path = TempDir::new("_rust_rocksdb_iterator_status_test").expect("");
+    let path_str = path.path().to_str().unwrap();
+    {
+        let db = DB::open_default(path_str).unwrap();
+        for i in 0..1000 {
+            db.put(format!("k{:04}", i).as_bytes(), b"v").unwrap();
+        }
+        db.flush(true).unwrap();
+
+        let mut iter = db.iter();
+        iter.seek(SeekKey::Start);
+        while iter.next() {}
+        assert!(iter.status().is_ok());
+    }
+
+    // Corrupt the data blocks at the beginning of the sst file.
+    for entry in fs::read_dir(path_str).unwrap() {
+        let p = entry.unwrap().path();
+        if p.extension().and_then(|e| e.to_str()) == Some("sst") {
+            let mut f = OpenOptions::new().write(true).open(&p).unwrap();
+            f.write_all(&[0xff; 64]).unwrap();
+        }
+    }
+
+    let db = DB::open_default(path_str).unwrap();
+    let mut iter = db.iter();
+    iter.seek(SeekKey::Start);
+    while iter.next() {}
+    assert!(!iter.valid());
+    assert!(iter.status().is_err());
+}
+
+#[test]
+fn test_iterator_borrowed_kv() {
+    let path = TempDir::new("_rust_rocksdb_iterator_borrowed_kv_test").expect("");
+    let db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+    for i in 0..100 {
+        db.put(format!("k{:03}", i).as_bytes(),
+                 format!("{:0>1024}", i).as_bytes())
+            .unwrap();
+    }
+
+    let mut iter = db.iter();
+    iter.seek(SeekKey::Start);
+    let mut count = 0;
+    while iter.valid() {
+        let (k, v) = iter.kv().unwrap();
+        assert_eq!(iter.key(), k.as_slice());
+        assert_eq!(iter.value(), v.as_slice());
+        assert_eq!(iter.key(), format!("k{:03}", count).as_bytes());
+        count += 1;
+        iter.next();
+    }
+    assert_eq!(count, 100);
+}
+
+#[test]
+fn test_iterator_with_readahead() {
+    let path = TempDir::new("_rust_rocksdb_iterator_readahead_test").expect("");
+    let db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+    for i in 0..10000 {
+        db.put(format!("k{:05}", i).as_bytes(), b"value").unwrap();
+    }
+    db.flush(true).unwrap();
+
+    let mut readopts = ReadOptions::new();
+    readopts.set_readahead_size(2 * 1024 * 1024);
+    let mut iter = db.iter_opt(readopts);
+    iter.seek(SeekKey::Start);
+    assert_eq!(iter.count(), 10000);
+}
+
+#[test]
+fn test_read_without_verify_checksums() {
+    let path = TempDir::new("_rust_rocksdb_read_without_verify_checksums_test").expect("");
+    let db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+    for i in 0..100 {
+        let k = format!("k{:03}", i);
+        db.put(k.as_bytes(), k.as_bytes()).unwrap();
+    }
+    db.flush(true).unwrap();
+
+    let mut readopts = ReadOptions::new();
+    readopts.set_verify_checksums(false);
+    assert_eq!(&*db.get_opt(b"k042", &readopts).unwrap().unwrap(), b"k042");
+
+    let mut readopts = ReadOptions::new();
+    readopts.set_verify_checksums(false);
+    let mut iter = db.iter_opt(readopts);
+    iter.seek(SeekKey::Start);
+    assert_eq!(iter.count(), 100);
+}
+
+#[test]
+fn test_ignore_range_deletions() {
+    let path = TempDir::new("_rust_rocksdb_ignore_range_deletions_test").expect("");
+    let db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+    for k in &[b"k1", b"k2", b"k3", b"k4", b"k5"] {
+        db.put(*k, b"v").unwrap();
+    }
+    db.delete_range(b"k2", b"k4").unwrap();
+    let batch = WriteBatch::new();
+    batch.delete_range(b"k5", b"k6").unwrap();
+    db.write(batch).unwrap();
+    assert!(db.get(b"k2").unwrap().is_none());
+    assert!(db.get(b"k3").unwrap().is_none());
+    assert!(db.get(b"k5").unwrap().is_none());
+
+    let keys = |readopts| {
+        let mut iter = db.iter_opt(readopts);
+        iter.seek(SeekKey::Start);
+        iter.map(|(k, _)| k).collect::<Vec<_>>()
+    };
+    assert_eq!(keys(ReadOptions::new()), vec![b"k1".to_vec(), b"k4".to_vec()]);
+
+    let mut readopts = ReadOptions::new();
+    readopts.set_ignore_range_deletions(true);
+    assert_eq!(keys(readopts),
+               vec![b"k1".to_vec(), b"k2".to_vec(), b"k3".to_vec(), b"k4".to_vec(),
+                    b"k5".to_vec()]);
+}
+
+#[test]
+fn test_read_options_reset_to_default() {
+    let path = TempDir::new("_rust_rocksdb_read_options_reset_to_default").expect("");
+    let db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+    db.put(b"k1", b"v").unwrap();
+    db.put(b"k2", b"v").unwrap();
+    let snap = unsafe { db.unsafe_snap() };
+    db.put(b"k3", b"v").unwrap();
+    db.put(b"k4", b"v").unwrap();
+    db.delete_range(b"k4", b"k5").unwrap();
+
+    let keys = |readopts| {
+        let mut iter = db.iter_opt(readopts);
+        iter.seek(SeekKey::Start);
+        iter.map(|(k, _)| k).collect::<Vec<_>>()
+    };
+    let expected = keys(ReadOptions::new());
+    assert_eq!(expected, vec![b"k1".to_vec(), b"k2".to_vec(), b"k3".to_vec()]);
+
+    let mut readopts = ReadOptions::new();
+    readopts.fill_cache(false);
+    readopts.set_verify_checksums(false);
+    unsafe {
+        readopts.set_snapshot(&snap);
+    }
+    readopts.set_iterate_upper_bound(b"k2");
+    readopts.set_ignore_range_deletions(true);
+    readopts.set_pin_data(true);
+    readopts.set_readahead_size(1024 * 1024);
+    readopts.set_background_purge_on_iterator_cleanup(true);
+    readopts.set_auto_prefix_mode(true);
+    readopts.set_deadline(1);
+    readopts.set_io_timeout(1);
+    // Reading the db without timestamps fails if any is left.
+    readopts.set_timestamp(1);
+    readopts.set_iter_start_ts(1);
+    readopts.reset_to_default();
+    assert_eq!(keys(readopts), expected);
+
+    // `reset` is the same, and the options can be set again afterwards.
+    let mut readopts = ReadOptions::new();
+    readopts.set_timestamp(1);
+    readopts.set_iterate_upper_bound(b"k2");
+    readopts.reset();
+    readopts.set_iterate_upper_bound(b"k3");
+    assert_eq!(keys(readopts), vec![b"k1".to_vec(), b"k2".to_vec()]);
+
+    unsafe {
+        db.release_snap(&snap);
+    }
+}
+
+#[test]
+fn test_pin_data() {
+    let path = TempDir::new("_rust_rocksdb_pin_data_test").expect("");
+    let db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+    for i in 0..1000 {
+        db.put(format!("k{:04}", i).as_bytes(), b"v").unwrap();
+    }
+    db.flush(true).unwrap();
+
+    let mut readopts = ReadOptions::new();
+    readopts.set_pin_data(true);
+    let mut iter = db.iter_opt(readopts);
+    iter.seek(SeekKey::Start);
+    let mut keys = vec![];
+    while iter.valid() {
+        let k = iter.key();
+        keys.push((k.as_ptr(), k.len()));
+        iter.next();
+    }
+    assert_eq!(keys.len(), 1000);
+    for (i, &(ptr, len)) in keys.iter().enumerate() {
+        let k = unsafe { slice::from_raw_parts(ptr, len) };
+        assert_eq!(k, format!("k{:04}", i).as_bytes());
+    }
+}
+
+#[test]
+fn test_background_purge_on_iterator_cleanup() {
+    let path = TempDir::new("_rust_rocksdb_background_purge_test").expect("");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.set_avoid_unnecessary_blocking_io(true);
+    let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    // Overlapping files, so they are not trivially moved by compaction.
+    for _ in 0..2 {
+        db.put(b"k1", b"v").unwrap();
+        db.put(b"k2", b"v").unwrap();
+        db.flush(true).unwrap();
+    }
+    let pinned = sst_files(path.path());
+    assert_eq!(pinned.len(), 2);
+
+    let mut readopts = ReadOptions::new();
+    readopts.set_background_purge_on_iterator_cleanup(true);
+    let mut iter = db.iter_opt(readopts);
+    iter.seek(SeekKey::Start);
+    assert!(iter.valid());
+
+    // The compacted files are still used by the iterator.
+    db.compact_range(None, None);
+    assert!(pinned.iter().all(|f| f.exists()));
+
+    let start = Instant::now();
+    drop(iter);
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while pinned.iter().any(|f| f.exists()) {
+        assert!(Instant::now() < deadline, "obsolete files are not purged");
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn test_iter_cfs() {
+    let path = TempDir::new("_rust_rocksdb_iter_cfs").expect("");
+    let mut db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+    db.create_cf("write", &Options::new()).unwrap();
+    let default = db.cf_handle("default").unwrap();
+    let write = db.cf_handle("write").unwrap();
+    db.put_cf(default, b"k1", b"d1").unwrap();
+    db.put_cf(write, b"k2", b"w2").unwrap();
+    db.put_cf(default, b"k3", b"d3").unwrap();
+    db.put_cf(write, b"k3", b"w3").unwrap();
+    db.put_cf(write, b"k4", b"w4").unwrap();
+    db.put_cf(default, b"k5", b"d5").unwrap();
+
+    let scan = |readopts: &ReadOptions, cfs: &[&CFHandle], key: SeekKey| {
+        let mut iter = db.iter_cfs(readopts, cfs).unwrap();
+        iter.seek(key);
+        let res = iter.collect::<Vec<_>>();
+        iter.status().unwrap();
+        res
+    };
+    let kv = |i: usize, k: &[u8], v: &[u8]| (i, k.to_vec(), v.to_vec());
+
+    let all = vec![kv(0, b"k1", b"d1"),
+                   kv(1, b"k2", b"w2"),
+                   kv(0, b"k3", b"d3"),
+                   kv(1, b"k3", b"w3"),
+                   kv(1, b"k4", b"w4"),
+                   kv(0, b"k5", b"d5")];
+    assert_eq!(scan(&ReadOptions::new(), &[default, write], SeekKey::Start), all);
+    assert_eq!(scan(&ReadOptions::new(), &[default, write], SeekKey::Key(b"k3")),
+               &all[2..]);
+    assert!(scan(&ReadOptions::new(), &[default, write], SeekKey::End).is_empty());
+
+    // Duplicate keys follow the order of the column families passed in.
+    let swapped = scan(&ReadOptions::new(), &[write, default], SeekKey::Start);
+    assert_eq!(swapped[2], kv(0, b"k3", b"w3"));
+    assert_eq!(swapped[3], kv(1, b"k3", b"d3"));
+
+    let mut readopts = ReadOptions::new();
+    readopts.set_iterate_upper_bound(b"k4");
+    assert_eq!(scan(&readopts, &[default, write], SeekKey::Start), &all[..4]);
+
+    let readopts = ReadOptions::new();
+    let mut iter = db.iter_cfs(&readopts, &[default, write]).unwrap();
+    assert!(iter.seek(SeekKey::Key(b"k2")));
+    assert_eq!((iter.cf_index(), iter.key(), iter.value()), (1, &b"k2"[..], &b"w2"[..]));
+    assert!(iter.advance());
+    assert_eq!((iter.cf_index(), iter.key()), (0, &b"k3"[..]));
+    // Seeking to the end leaves nothing to iterate.
+    assert!(!iter.seek(SeekKey::End));
+    assert!(!iter.advance());
+}
+
+#[test]
+fn test_iterator_refresh() {
+    let path = TempDir::new("_rust_rocksdb_iterator_refresh").expect("");
+    let db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+    db.put(b"k1", b"v1").unwrap();
+    db.flush(true).unwrap();
+    db.put(b"k2", b"v2").unwrap();
+
+    let mut iter = db.iter();
+    db.put(b"k3", b"v3").unwrap();
+    db.flush(true).unwrap();
+    db.delete(b"k1").unwrap();
+    iter.seek(SeekKey::Start);
+    let keys: Vec<_> = iter.map(|(k, _)| k).collect();
+    assert_eq!(keys, vec![b"k1".to_vec(), b"k2".to_vec()]);
+
+    iter.refresh().unwrap();
+    iter.seek(SeekKey::Start);
+    let keys: Vec<_> = iter.map(|(k, _)| k).collect();
+    assert_eq!(keys, vec![b"k2".to_vec(), b"k3".to_vec()]);
+}
+
+#[test]
+fn test_auto_prefix_mode() {
+    let path = TempDir::new("_rust_rocksdb_auto_prefix_mode").expect("");
+    let db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+    for i in 0..100 {
+        db.put(format!("k{:03}", i).as_bytes(), b"v").unwrap();
+        if i % 25 == 24 {
+            db.flush(true).unwrap();
+        }
+    }
+
+    let scan = |auto_prefix_mode| {
+        let mut readopts = ReadOptions::new();
+        readopts.set_auto_prefix_mode(auto_prefix_mode);
+        readopts.set_iterate_upper_bound(b"k040");
+        let mut iter = db.iter_opt(readopts);
+        iter.seek(SeekKey::Key(b"k030"));
+        iter.map(|(k, _)| k).collect::<Vec<_>>()
+    };
+    let expected: Vec<_> = (30..40).map(|i| format!("k{:03}", i).into_bytes()).collect();
+    assert_eq!(scan(false), expected);
+    assert_eq!(scan(true), expected);
+}
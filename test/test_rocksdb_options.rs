@@ -1,5 +1,18 @@
 
-use rocksdb::{DB, Options};use tempdir::TempDir;
+use rocksdb::{DB, Options, BlockBasedOptions, Writable, DBChecksumType, DBDataBlockIndexType,
+              DBInfoLogLevel, DBPrepopulateBlockCache, DBRateLimiterMode, DBRecoveryMode, SeekKey,
+              Env, Logger, RateLimiter, ReadOptions, WriteOptions, DBCompressionType,
+              DBStatisticsHistogramType, DBStatisticsTickerType, DB_LOCKED_ERROR_PREFIX,
+              load_latest_options};
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tempdir::TempDir;
+use util::sst_files;
 
 
 #[test]
@@ -22,3 +35,974 @@ fn test_set_ratelimiter() {
     let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
     drop(db);
 }
+
+#[test]
+fn test_set_rate_limiter_with_mode() {
+    let path = TempDir::new("_rust_rocksdb_test_set_rate_limiter_with_mode").expect("");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    let limiter = RateLimiter::new_with_mode(10 * 1024 * 1024,
+                                             100 * 1000,
+                                             10,
+                                             DBRateLimiterMode::ReadsOnly,
+                                             false);
+    opts.set_rate_limiter(&limiter);
+    drop(limiter);
+    let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    for i in 0..100 {
+        db.put(format!("k{}", i).as_bytes(), b"v").unwrap();
+    }
+    db.flush(true).unwrap();
+    for i in 0..100 {
+        assert!(db.get(format!("k{}", i).as_bytes()).unwrap().is_some());
+    }
+}
+
+#[test]
+fn test_set_auto_tuned_rate_limiter() {
+    let path = TempDir::new("_rust_rocksdb_test_set_auto_tuned_rate_limiter").expect("");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    let limiter = RateLimiter::new_auto_tuned(100 * 1024 * 1024, 100 * 1000, 10);
+    opts.set_rate_limiter(&limiter);
+    let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    for i in 0..100 {
+        db.put(format!("k{}", i).as_bytes(), b"v").unwrap();
+    }
+    db.flush(true).unwrap();
+    db.compact_range(None, None);
+    for i in 0..100 {
+        assert!(db.get(format!("k{}", i).as_bytes()).unwrap().is_some());
+    }
+}
+
+#[test]
+fn test_pin_l0_filter_and_index_blocks_in_cache() {
+    let path = TempDir::new("_rust_rocksdb_test_pin_l0_filter_and_index_blocks").expect("");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    let mut block_opts = BlockBasedOptions::new();
+    block_opts.set_lru_cache(8 * 1024 * 1024);
+    block_opts.set_bloom_filter(10, false);
+    block_opts.set_cache_index_and_filter_blocks(true);
+    block_opts.set_pin_l0_filter_and_index_blocks_in_cache(true);
+    block_opts.set_pin_top_level_index_and_filter(true);
+    opts.set_block_based_table_factory(&block_opts);
+    let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    db.put(b"k1", b"v1").unwrap();
+    db.flush(true).unwrap();
+
+    // Reading from the L0 file loads its index and filter into the block
+    // cache, where they stay pinned.
+    assert_eq!(db.get(b"k1").unwrap().unwrap().to_utf8().unwrap(), "v1");
+    assert!(db.get(b"k2").unwrap().is_none());
+    let usage = db.get_property_int("rocksdb.block-cache-usage").unwrap();
+    assert!(usage > 0);
+}
+
+fn open_with_format_version(path: &TempDir, version: u32) -> DB {
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    let mut block_opts = BlockBasedOptions::new();
+    block_opts.set_format_version(version);
+    block_opts.set_checksum(DBChecksumType::XxHash64);
+    block_opts.set_block_restart_interval(4);
+    block_opts.set_index_block_restart_interval(2);
+    opts.set_block_based_table_factory(&block_opts);
+    DB::open(opts, path.path().to_str().unwrap()).unwrap()
+}
+
+#[test]
+fn test_block_based_format_version_and_checksum() {
+    let mut verify_opts = ReadOptions::new();
+    verify_opts.set_verify_checksums(true);
+    for &version in &[4, 5] {
+        let path = TempDir::new("_rust_rocksdb_test_format_version_and_checksum").expect("");
+        {
+            let db = open_with_format_version(&path, version);
+            for i in 0..100 {
+                let k = format!("k{:03}", i);
+                db.put(k.as_bytes(), k.as_bytes()).unwrap();
+            }
+            db.flush(true).unwrap();
+        }
+
+        {
+            let db = open_with_format_version(&path, version);
+            for i in 0..100 {
+                let k = format!("k{:03}", i);
+                let v = db.get_opt(k.as_bytes(), &verify_opts).unwrap().unwrap();
+                assert_eq!(v.to_utf8().unwrap(), k);
+            }
+        }
+
+        // The data block starts the file, a flipped byte in it must fail
+        // the checksum.
+        let sst = sst_files(path.path()).pop().unwrap();
+        let mut data = fs::read(&sst).unwrap();
+        data[10] ^= 0xff;
+        fs::write(&sst, data).unwrap();
+        let db = open_with_format_version(&path, version);
+        match db.get_opt(b"k000", &verify_opts) {
+            Err(e) => assert!(e.contains("checksum mismatch"), "unexpected error: {}", e),
+            Ok(_) => panic!("format_version {} should fail the checksum", version),
+        }
+    }
+}
+
+#[test]
+fn test_unsupported_format_version() {
+    let path = TempDir::new("_rust_rocksdb_test_unsupported_format_version").expect("");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    let mut block_opts = BlockBasedOptions::new();
+    block_opts.set_format_version(99);
+    opts.set_block_based_table_factory(&block_opts);
+    match DB::open(opts, path.path().to_str().unwrap()) {
+        Err(e) => assert!(e.contains("format_version"), "unexpected error: {}", e),
+        Ok(_) => panic!("should fail to open with format_version 99"),
+    }
+}
+
+#[test]
+fn test_data_block_hash_index() {
+    let path = TempDir::new("_rust_rocksdb_test_data_block_hash_index").expect("");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    let mut block_opts = BlockBasedOptions::new();
+    block_opts.set_data_block_index_type(DBDataBlockIndexType::BinaryAndHash);
+    block_opts.set_data_block_hash_table_util_ratio(0.75);
+    opts.set_block_based_table_factory(&block_opts);
+    let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    let n = 100_000;
+    for i in 0..n {
+        let k = format!("k{:06}", i);
+        db.put(k.as_bytes(), format!("v{}", i).as_bytes()).unwrap();
+    }
+    db.flush(true).unwrap();
+
+    for i in (0..n).filter(|i| i % 7 == 0) {
+        let k = format!("k{:06}", i);
+        let v = format!("v{}", i);
+        assert_eq!(db.get(k.as_bytes()).unwrap().unwrap().to_utf8().unwrap(), v);
+    }
+    assert!(db.get(b"k1000000").unwrap().is_none());
+
+    let mut iter = db.iter();
+    iter.seek(SeekKey::Start);
+    let mut count = 0;
+    for (k, v) in &mut iter {
+        assert_eq!(k, format!("k{:06}", count).into_bytes());
+        assert_eq!(v, format!("v{}", count).into_bytes());
+        count += 1;
+    }
+    assert_eq!(count, n);
+
+    // The table options in use are written to the OPTIONS file.
+    let content = latest_options_file(path.path());
+    let option = |name: &str| {
+        let prefix = format!("{}=", name);
+        content.lines()
+            .map(|l| l.trim())
+            .find(|l| l.starts_with(&prefix))
+            .map(|l| l[prefix.len()..].to_owned())
+            .unwrap()
+    };
+    assert_eq!(option("data_block_index_type"), "kDataBlockBinaryAndHash");
+    assert_eq!(option("data_block_hash_table_util_ratio").parse::<f64>().unwrap(), 0.75);
+}
+
+#[test]
+fn test_data_block_hash_index_point_lookups() {
+    let path = TempDir::new("_rust_rocksdb_test_data_block_hash_index_point_lookups").expect("");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    let mut block_opts = BlockBasedOptions::new();
+    block_opts.set_data_block_index_type(DBDataBlockIndexType::BinaryAndHash);
+    opts.set_block_based_table_factory(&block_opts);
+    let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    for i in 0..1000 {
+        db.put(format!("k{:04}", i * 2).as_bytes(), b"old").unwrap();
+    }
+    db.flush(true).unwrap();
+    // Newer versions and deletes in a second table shadow the first one.
+    for i in (0..1000).filter(|i| i % 3 == 0) {
+        db.put(format!("k{:04}", i * 2).as_bytes(), b"new").unwrap();
+    }
+    for i in (0..1000).filter(|i| i % 5 == 0) {
+        db.delete(format!("k{:04}", i * 2).as_bytes()).unwrap();
+    }
+    db.flush(true).unwrap();
+
+    for i in 0..1000 {
+        let v = db.get(format!("k{:04}", i * 2).as_bytes()).unwrap();
+        if i % 5 == 0 {
+            assert!(v.is_none());
+        } else if i % 3 == 0 {
+            assert_eq!(v.unwrap(), &b"new"[..]);
+        } else {
+            assert_eq!(v.unwrap(), &b"old"[..]);
+        }
+        // The odd keys fall between the keys of a block and miss the hash.
+        assert!(db.get(format!("k{:04}", i * 2 + 1).as_bytes()).unwrap().is_none());
+    }
+}
+
+#[test]
+fn test_ribbon_filter() {
+    let path = TempDir::new("_rust_rocksdb_test_ribbon_filter").expect("");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.enable_statistics();
+    let mut block_opts = BlockBasedOptions::new();
+    block_opts.set_hybrid_ribbon_filter(10.0, 1);
+    opts.set_block_based_table_factory(&block_opts);
+    let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    for i in 0..1000 {
+        let k = format!("k{:04}", i);
+        db.put(k.as_bytes(), k.as_bytes()).unwrap();
+    }
+    db.flush(true).unwrap();
+    db.compact_range(None, None);
+
+    for i in 0..1000 {
+        let k = format!("k{:04}", i);
+        assert_eq!(db.get(k.as_bytes()).unwrap().unwrap().to_utf8().unwrap(), k);
+    }
+    for i in 0..1000 {
+        let k = format!("k{:04}-absent", i);
+        assert!(db.get(k.as_bytes()).unwrap().is_none());
+    }
+    assert!(db.get_statistics_ticker_count(DBStatisticsTickerType::BloomFilterUseful).unwrap() > 0);
+}
+
+#[test]
+fn test_no_block_cache() {
+    let path = TempDir::new("_rust_rocksdb_test_no_block_cache").expect("");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.enable_statistics();
+    let mut block_opts = BlockBasedOptions::new();
+    block_opts.set_no_block_cache(true);
+    block_opts.set_block_size_deviation(5);
+    opts.set_block_based_table_factory(&block_opts);
+    let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    for i in 0..100 {
+        let k = format!("k{:03}", i);
+        db.put(k.as_bytes(), k.as_bytes()).unwrap();
+    }
+    db.flush(true).unwrap();
+    for i in 0..100 {
+        let k = format!("k{:03}", i);
+        assert_eq!(db.get(k.as_bytes()).unwrap().unwrap().to_utf8().unwrap(), k);
+    }
+    assert_eq!(db.get_statistics_ticker_count(DBStatisticsTickerType::BlockCacheAdd), Some(0));
+}
+
+fn block_cache_data_misses_after_flush(path: &TempDir,
+                                       prepopulate: DBPrepopulateBlockCache)
+                                       -> u64 {
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.enable_statistics();
+    let mut block_opts = BlockBasedOptions::new();
+    block_opts.set_prepopulate_block_cache(prepopulate);
+    opts.set_block_based_table_factory(&block_opts);
+    let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    for i in 0..10000 {
+        let k = format!("k{:05}", i);
+        db.put(k.as_bytes(), k.as_bytes()).unwrap();
+    }
+    db.flush(true).unwrap();
+    let misses = || {
+        db.get_statistics_ticker_count(DBStatisticsTickerType::BlockCacheDataMiss).unwrap()
+    };
+    let before = misses();
+    for i in 0..10000 {
+        let k = format!("k{:05}", i);
+        assert_eq!(db.get(k.as_bytes()).unwrap().unwrap().to_utf8().unwrap(), k);
+    }
+    misses() - before
+}
+
+#[test]
+fn test_prepopulate_block_cache() {
+    let path = TempDir::new("_rust_rocksdb_test_prepopulate_block_cache").expect("");
+    let misses = block_cache_data_misses_after_flush(&path, DBPrepopulateBlockCache::FlushOnly);
+    let path = TempDir::new("_rust_rocksdb_test_prepopulate_block_cache_disabled").expect("");
+    let control = block_cache_data_misses_after_flush(&path, DBPrepopulateBlockCache::Disabled);
+    // Every data block is read from the file once without prepopulating.
+    assert!(control > 0);
+    assert_eq!(misses, 0);
+}
+
+#[test]
+fn test_read_amp_tickers() {
+    let path = TempDir::new("_rust_rocksdb_test_read_amp_tickers").expect("");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.enable_statistics();
+    let mut block_opts = BlockBasedOptions::new();
+    block_opts.set_block_size(64 * 1024);
+    opts.set_block_based_table_factory(&block_opts);
+    let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    for i in 0..10000 {
+        let k = format!("k{:05}", i);
+        db.put(k.as_bytes(), k.as_bytes()).unwrap();
+    }
+    db.flush(true).unwrap();
+    for i in (0..10000).filter(|i| i % 1000 == 0) {
+        let k = format!("k{:05}", i);
+        assert_eq!(db.get(k.as_bytes()).unwrap().unwrap().to_utf8().unwrap(), k);
+    }
+
+    let ticker = |t| db.get_statistics_ticker_count(t).unwrap();
+    assert!(ticker(DBStatisticsTickerType::BlockCacheDataMiss) > 0);
+    assert!(ticker(DBStatisticsTickerType::BlockCacheBytesWrite) > 0);
+    // The read amp estimation is only done with
+    // `BlockBasedTableOptions::read_amp_bytes_per_bit`, which the C API
+    // has no setter for.
+    assert_eq!(ticker(DBStatisticsTickerType::ReadAmpEstimateUsefulBytes), 0);
+    assert_eq!(ticker(DBStatisticsTickerType::ReadAmpTotalReadBytes), 0);
+
+    let opts = Options::new();
+    assert!(opts.get_statistics_ticker_count(DBStatisticsTickerType::BlockCacheDataMiss).is_none());
+}
+
+#[test]
+fn test_statistics_histogram() {
+    let path = TempDir::new("_rust_rocksdb_test_statistics_histogram").expect("");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.enable_statistics();
+    let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    db.put(b"k1", b"v1").unwrap();
+    let n = 100;
+    for _ in 0..n {
+        assert_eq!(&*db.get(b"k1").unwrap().unwrap(), b"v1");
+    }
+
+    let hist = db.get_statistics_histogram(DBStatisticsHistogramType::DbGet);
+    assert_eq!(hist.count(), n);
+    assert!(hist.max() >= hist.median());
+    assert!(hist.percentile99() >= hist.median());
+    assert!(hist.sum() as f64 >= hist.average());
+    assert!(hist.standard_deviation() >= 0.0);
+
+    let s = db.get_statistics_histogram_string(DBStatisticsHistogramType::DbGet).unwrap();
+    assert!(s.starts_with("rocksdb.db.get.micros "));
+    // The line is made of "NAME : VALUE" pairs, with the count among them.
+    let fields: Vec<_> = s["rocksdb.db.get.micros ".len()..].split(" : ").collect();
+    assert!(fields.len() > 2);
+    let count = s.split("COUNT : ").nth(1).unwrap().split(' ').next().unwrap();
+    assert_eq!(count.parse::<u64>().unwrap(), n);
+
+    // Everything is 0 without statistics.
+    let opts = Options::new();
+    assert_eq!(opts.get_statistics_histogram(DBStatisticsHistogramType::DbGet).count(), 0);
+    assert!(opts.get_statistics_histogram_string(DBStatisticsHistogramType::DbGet).is_none());
+}
+
+// Restores the thread pools of the default env, which is shared by all the
+// tests in the process.
+struct BackgroundThreadsGuard {
+    env: Arc<Env>,
+    low: i32,
+    high: i32,
+}
+
+impl Drop for BackgroundThreadsGuard {
+    fn drop(&mut self) {
+        self.env.set_background_threads(self.low);
+        self.env.set_high_priority_background_threads(self.high);
+    }
+}
+
+#[test]
+fn test_env_background_threads() {
+    let path = TempDir::new("_rust_rocksdb_test_env_background_threads").expect("");
+    let env = Arc::new(Env::default());
+    let _guard = BackgroundThreadsGuard {
+        env: env.clone(),
+        low: env.get_background_threads(),
+        high: env.get_high_priority_background_threads(),
+    };
+    env.set_background_threads(1);
+    env.set_high_priority_background_threads(1);
+    // The priorities can't be restored, lowering them only makes the
+    // background work of the other tests scheduled later.
+    env.lower_thread_pool_io_priority();
+    env.lower_high_priority_thread_pool_io_priority();
+    env.lower_thread_pool_cpu_priority();
+    env.lower_high_priority_thread_pool_cpu_priority();
+    assert_eq!(env.get_background_threads(), 1);
+    assert_eq!(env.get_high_priority_background_threads(), 1);
+
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.set_env(env.clone());
+    opts.set_level_zero_file_num_compaction_trigger(2);
+    let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    for i in 0..4 {
+        db.put(format!("k{}", i).as_bytes(), b"v").unwrap();
+        db.flush(true).unwrap();
+    }
+    db.compact_range(None, None);
+    assert_eq!(db.get_property_int("rocksdb.num-files-at-level0").unwrap(), 0);
+    for i in 0..4 {
+        assert!(db.get(format!("k{}", i).as_bytes()).unwrap().is_some());
+    }
+}
+
+fn count_files_with_ext(dir: &Path, ext: &str) -> usize {
+    fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some(ext))
+        .count()
+}
+
+#[test]
+fn test_set_wal_dir_and_db_paths() {
+    let path = TempDir::new("_rust_rocksdb_test_set_wal_dir_and_db_paths").expect("");
+    let db_dir = path.path().join("db");
+    let wal_dir = path.path().join("wal");
+    let sst_dir = path.path().join("sst");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.set_wal_dir(wal_dir.to_str().unwrap());
+    opts.set_db_paths(&[(sst_dir.to_str().unwrap(), 1024 * 1024 * 1024)]);
+    let db = DB::open(opts, db_dir.to_str().unwrap()).unwrap();
+    db.put(b"k1", b"v1").unwrap();
+    assert!(count_files_with_ext(&wal_dir, "log") > 0);
+    assert_eq!(count_files_with_ext(&db_dir, "log"), 0);
+
+    db.flush(true).unwrap();
+    assert!(count_files_with_ext(&sst_dir, "sst") > 0);
+    assert_eq!(count_files_with_ext(&db_dir, "sst"), 0);
+    assert_eq!(db.get(b"k1").unwrap().unwrap().to_utf8().unwrap(), "v1");
+}
+
+struct CollectLogger {
+    lines: Arc<Mutex<Vec<String>>>,
+}
+
+impl Logger for CollectLogger {
+    fn logv(&self, _: DBInfoLogLevel, msg: &str) {
+        self.lines.lock().unwrap().push(msg.to_owned());
+    }
+}
+
+#[test]
+fn test_set_logger() {
+    let path = TempDir::new("_rust_rocksdb_test_set_logger").expect("");
+    let lines = Arc::new(Mutex::new(vec![]));
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.set_info_log_level(DBInfoLogLevel::Info);
+    opts.set_logger(DBInfoLogLevel::Info,
+                    Box::new(CollectLogger { lines: lines.clone() }));
+    let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    assert!(!lines.lock().unwrap().is_empty());
+    // The info log no longer goes to the LOG file.
+    assert!(!path.path().join("LOG").exists());
+    drop(db);
+}
+
+fn wal_size(dir: &Path) -> u64 {
+    fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("log"))
+        .map(|p| fs::metadata(p).unwrap().len())
+        .sum()
+}
+
+#[test]
+fn test_write_options_reset_to_default() {
+    let path = TempDir::new("_rust_rocksdb_test_write_options_reset_to_default").expect("");
+    let db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+    let mut writeopts = WriteOptions::new();
+    writeopts.set_sync(true);
+    writeopts.disable_wal(true);
+    db.put_opt(b"k1", b"v1", &writeopts).unwrap();
+    assert_eq!(wal_size(path.path()), 0);
+
+    writeopts.reset_to_default();
+    db.put_opt(b"k2", b"v2", &writeopts).unwrap();
+    assert!(wal_size(path.path()) > 0);
+    assert!(db.get(b"k1").unwrap().is_some());
+    assert!(db.get(b"k2").unwrap().is_some());
+}
+
+fn latest_options_file(dir: &Path) -> String {
+    let mut files: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .filter(|n| n.starts_with("OPTIONS-"))
+        .collect();
+    files.sort_by_key(|n| n["OPTIONS-".len()..].parse::<u64>().unwrap());
+    let mut content = String::new();
+    fs::File::open(dir.join(files.last().unwrap()))
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    content
+}
+
+#[test]
+fn test_load_latest_options() {
+    let path = TempDir::new("_rust_rocksdb_test_load_latest_options").expect("");
+    let path_str = path.path().to_str().unwrap();
+    {
+        let mut opts = Options::new();
+        opts.create_if_missing(true);
+        let mut db = DB::open(opts, path_str).unwrap();
+        let mut cf_opts = Options::new();
+        let mut block_opts = BlockBasedOptions::new();
+        block_opts.set_block_size(32 * 1024);
+        cf_opts.set_block_based_table_factory(&block_opts);
+        cf_opts.compression_per_level(&[DBCompressionType::DBNo,
+                                        DBCompressionType::DBNo,
+                                        DBCompressionType::DBSnappy]);
+        db.create_cf("cf1", &cf_opts).unwrap();
+    }
+    let check_options_file = || {
+        let content = latest_options_file(path.path());
+        assert!(content.contains("block_size=32768"));
+        assert!(content.contains("compression_per_level=\
+                                  kNoCompression:kNoCompression:kSnappyCompression"));
+    };
+    check_options_file();
+
+    let env = Env::default();
+    let (db_opts, cfs) = load_latest_options(path_str, &env, false).unwrap();
+    let names: Vec<&str> = cfs.iter().map(|&(ref n, _)| n.as_str()).collect();
+    assert_eq!(names, vec!["default", "cf1"]);
+    let cf_opts: Vec<&Options> = cfs.iter().map(|&(_, ref o)| o).collect();
+    {
+        let db = DB::open_cf(db_opts, path_str, &names, &cf_opts).unwrap();
+        db.put_cf(db.cf_handle("cf1").unwrap(), b"k1", b"v1").unwrap();
+    }
+    // Reopening writes a new OPTIONS file from the loaded options.
+    check_options_file();
+
+    let e = load_latest_options(path.path().join("nonexistent").to_str().unwrap(), &env, true);
+    assert!(e.is_err());
+}
+
+#[test]
+fn test_allow_mmap_reads() {
+    let path = TempDir::new("_rust_rocksdb_test_allow_mmap_reads").expect("");
+    let path_str = path.path().to_str().unwrap();
+    let new_opts = || {
+        let mut opts = Options::new();
+        opts.create_if_missing(true);
+        opts.set_allow_mmap_reads(true);
+        opts.set_advise_random_on_open(false);
+        opts.set_is_fd_close_on_exec(true);
+        opts.set_max_file_opening_threads(4);
+        opts
+    };
+    {
+        let db = DB::open(new_opts(), path_str).unwrap();
+        for i in 0..100 {
+            db.put(format!("k{}", i).as_bytes(), b"v").unwrap();
+        }
+        db.flush(true).unwrap();
+        assert_eq!(db.get(b"k1").unwrap().unwrap(), &b"v"[..]);
+    }
+    let db = DB::open(new_opts(), path_str).unwrap();
+    for i in 0..100 {
+        assert_eq!(db.get(format!("k{}", i).as_bytes()).unwrap().unwrap(), &b"v"[..]);
+    }
+}
+
+#[test]
+fn test_allow_mmap_writes() {
+    let path = TempDir::new("_rust_rocksdb_test_allow_mmap_writes").expect("");
+    let path_str = path.path().to_str().unwrap();
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.set_allow_mmap_writes(true);
+    let db = DB::open(opts, path_str).unwrap();
+    db.put(b"k1", b"v1").unwrap();
+    db.flush(true).unwrap();
+    assert_eq!(db.get(b"k1").unwrap().unwrap(), &b"v1"[..]);
+}
+
+#[test]
+fn test_direct_io() {
+    let path = TempDir::new("_rust_rocksdb_test_direct_io").expect("");
+    let path_str = path.path().to_str().unwrap();
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.set_use_direct_reads(true);
+    opts.set_use_direct_io_for_flush_and_compaction(true);
+    let db = match DB::open(opts, path_str) {
+        Ok(db) => db,
+        // Some file systems such as tmpfs don't support O_DIRECT, which
+        // RocksDB checks when the db is opened.
+        Err(ref e) if e == "Invalid argument: Direct I/O is not supported by the specified DB." => {
+            return;
+        }
+        Err(e) => panic!("{}", e),
+    };
+    for i in 0..100 {
+        db.put(format!("k{}", i).as_bytes(), b"v").unwrap();
+        if i % 10 == 9 {
+            db.flush(true).unwrap();
+        }
+    }
+    db.compact_range(None, None);
+    for i in 0..100 {
+        assert_eq!(db.get(format!("k{}", i).as_bytes()).unwrap().unwrap(), &b"v"[..]);
+    }
+}
+
+#[test]
+fn test_mmap_conflicts_with_direct_io() {
+    let path = TempDir::new("_rust_rocksdb_test_mmap_conflicts_with_direct_io").expect("");
+    let path_str = path.path().to_str().unwrap();
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.set_allow_mmap_reads(true);
+    opts.set_use_direct_reads(true);
+    assert!(DB::open(opts, path_str).is_err());
+
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.set_allow_mmap_writes(true);
+    opts.set_use_direct_io_for_flush_and_compaction(true);
+    assert!(DB::open(opts, path_str).is_err());
+}
+
+#[test]
+fn test_bytes_per_sync() {
+    let path = TempDir::new("_rust_rocksdb_test_bytes_per_sync").expect("");
+    let path_str = path.path().to_str().unwrap();
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.set_bytes_per_sync(1024 * 1024);
+    opts.set_wal_bytes_per_sync(512 * 1024);
+    assert_eq!(opts.get_bytes_per_sync(), 1024 * 1024);
+    assert_eq!(opts.get_wal_bytes_per_sync(), 512 * 1024);
+    {
+        let db = DB::open(opts, path_str).unwrap();
+        let value = vec![b'v'; 1024];
+        for i in 0..10000 {
+            db.put(format!("k{:05}", i).as_bytes(), &value).unwrap();
+        }
+        db.flush(true).unwrap();
+    }
+    let content = latest_options_file(path.path());
+    assert!(content.contains("bytes_per_sync=1048576"));
+    assert!(content.contains("wal_bytes_per_sync=524288"));
+
+    let (db_opts, _) = load_latest_options(path_str, &Env::default(), false).unwrap();
+    assert_eq!(db_opts.get_bytes_per_sync(), 1024 * 1024);
+    assert_eq!(db_opts.get_wal_bytes_per_sync(), 512 * 1024);
+    let db = DB::open(db_opts, path_str).unwrap();
+    for i in 0..10000 {
+        assert_eq!(db.get(format!("k{:05}", i).as_bytes()).unwrap().unwrap().len(),
+                   1024);
+    }
+}
+
+#[test]
+fn test_stats_persist_period_sec() {
+    let path = TempDir::new("_rust_rocksdb_test_stats_persist_period_sec").expect("");
+    let path_str = path.path().to_str().unwrap();
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.enable_statistics();
+    opts.set_stats_persist_period_sec(5);
+    assert_eq!(opts.get_stats_persist_period_sec(), 5);
+    {
+        let db = DB::open(opts, path_str).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+    }
+    let content = latest_options_file(path.path());
+    assert!(content.contains("stats_persist_period_sec=5"));
+
+    let (db_opts, _) = load_latest_options(path_str, &Env::default(), false).unwrap();
+    assert_eq!(db_opts.get_stats_persist_period_sec(), 5);
+    let db = DB::open(db_opts, path_str).unwrap();
+    assert_eq!(&*db.get(b"k1").unwrap().unwrap(), b"v1");
+}
+
+#[test]
+fn test_max_write_batch_group_size_bytes() {
+    let path = TempDir::new("_rust_rocksdb_test_max_write_batch_group_size_bytes").expect("");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.set_max_write_batch_group_size_bytes(64 * 1024);
+    let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    db.put(b"k1", b"v1").unwrap();
+    let content = latest_options_file(path.path());
+    assert!(content.contains("max_write_batch_group_size_bytes=65536"));
+}
+
+#[test]
+fn test_track_and_verify_wals_in_manifest() {
+    let path = TempDir::new("_rust_rocksdb_test_track_and_verify_wals_in_manifest").expect("");
+    let path_str = path.path().to_str().unwrap();
+    let mut opts = Options::new();
+    assert!(!opts.get_track_and_verify_wals_in_manifest());
+    opts.create_if_missing(true);
+    opts.set_track_and_verify_wals_in_manifest(true);
+    opts.set_wal_recovery_mode(DBRecoveryMode::PointInTime);
+    assert!(opts.get_track_and_verify_wals_in_manifest());
+    {
+        let db = DB::open(opts, path_str).unwrap();
+        let mut write_opts = WriteOptions::new();
+        write_opts.set_sync(true);
+        db.put_opt(b"k1", b"v1", &write_opts).unwrap();
+    }
+    let content = latest_options_file(path.path());
+    assert!(content.contains("track_and_verify_wals_in_manifest=true"));
+
+    // The synced WAL is recovered with the tracking on.
+    let (db_opts, _) = load_latest_options(path_str, &Env::default(), false).unwrap();
+    assert!(db_opts.get_track_and_verify_wals_in_manifest());
+    let db = DB::open(db_opts, path_str).unwrap();
+    assert_eq!(&*db.get(b"k1").unwrap().unwrap(), b"v1");
+}
+
+#[test]
+fn test_paranoid_checks_with_missing_sst() {
+    let path = TempDir::new("_rust_rocksdb_test_paranoid_checks").expect("");
+    let path_str = path.path().to_str().unwrap();
+    let new_opts = || {
+        let mut opts = Options::new();
+        opts.create_if_missing(true);
+        opts.set_paranoid_checks(true);
+        opts.set_skip_stats_update_on_db_open(true);
+        opts.set_disable_auto_compactions(true);
+        opts
+    };
+    {
+        let db = DB::open(new_opts(), path_str).unwrap();
+        for i in 0..3 {
+            db.put(format!("k{}", i).as_bytes(), b"v").unwrap();
+            db.flush(true).unwrap();
+        }
+    }
+    // Stats are only skipped, the data is intact.
+    {
+        let db = DB::open(new_opts(), path_str).unwrap();
+        assert_eq!(&*db.get(b"k1").unwrap().unwrap(), b"v");
+    }
+
+    assert_eq!(count_files_with_ext(path.path(), "sst"), 3);
+    let sst = fs::read_dir(path.path())
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .find(|p| p.extension().and_then(|e| e.to_str()) == Some("sst"))
+        .unwrap();
+    fs::remove_file(sst).unwrap();
+    assert!(DB::open(new_opts(), path_str).is_err());
+}
+
+#[test]
+fn test_prepare_for_bulk_load() {
+    let path = TempDir::new("_rust_rocksdb_test_prepare_for_bulk_load").expect("");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.prepare_for_bulk_load();
+    let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    for round in 0..4 {
+        for i in 0..1000 {
+            let k = format!("k{:04}", i * 4 + round);
+            db.put(k.as_bytes(), b"v").unwrap();
+        }
+        db.flush(true).unwrap();
+    }
+    // Nothing is compacted during the load.
+    assert_eq!(db.get_property_int("rocksdb.num-files-at-level0"), Some(4));
+
+    db.compact_range(None, None);
+    assert_eq!(db.get_property_int("rocksdb.num-files-at-level0"), Some(0));
+    let mut iter = db.iter();
+    iter.seek(SeekKey::Start);
+    assert_eq!(iter.count(), 4000);
+}
+
+#[test]
+fn test_read_deadline_and_io_timeout() {
+    let path = TempDir::new("_rust_rocksdb_test_read_deadline").expect("");
+    let path_str = path.path().to_str().unwrap();
+    {
+        let db = DB::open_default(path_str).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        db.flush(true).unwrap();
+    }
+
+    // Reopen so the data block has to be read from the sst file.
+    let db = DB::open_default(path_str).unwrap();
+    let mut expired = ReadOptions::new();
+    expired.set_deadline(1);
+    let err = db.get_opt(b"k1", &expired).unwrap_err();
+    assert!(err.starts_with("Operation timed out"), "{}", err);
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let mut bounded = ReadOptions::new();
+    bounded.set_deadline(now.as_secs() * 1_000_000 + 60_000_000);
+    bounded.set_io_timeout(10_000_000);
+    assert_eq!(&*db.get_opt(b"k1", &bounded).unwrap().unwrap(), b"v1");
+    assert_eq!(&*db.get(b"k1").unwrap().unwrap(), b"v1");
+}
+
+fn open_with_50_ssts(path: &TempDir, max_open_files: i32) -> DB {
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.set_max_open_files(max_open_files);
+    opts.set_max_file_opening_threads(2);
+    opts.set_disable_auto_compactions(true);
+    let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    for i in 0..50 {
+        db.put(format!("k{:02}", i).as_bytes(), b"v").unwrap();
+        db.flush(true).unwrap();
+    }
+    assert_eq!(count_files_with_ext(path.path(), "sst"), 50);
+    db
+}
+
+#[test]
+fn test_max_open_files() {
+    let path = TempDir::new("_rust_rocksdb_test_max_open_files").expect("");
+    let db = open_with_50_ssts(&path, 20);
+    let cf = db.cf_handle("default").unwrap();
+    let read = |keys: &mut dyn Iterator<Item = i32>| {
+        for i in keys {
+            assert!(db.get(format!("k{:02}", i).as_bytes()).unwrap().is_some());
+        }
+        db.get_table_readers_mem_cf(cf).unwrap()
+    };
+    // Only 10 tables fit in the table cache, the 10 others of the 20 open
+    // files are reserved, so the readers stop growing after 10 files.
+    let first_half = read(&mut (0..25));
+    let all = read(&mut (25..50));
+    assert!(first_half > 0);
+    assert!(all <= first_half + first_half / 10, "{} {}", first_half, all);
+
+    let mut iter = db.iter();
+    iter.seek(SeekKey::Start);
+    let keys: Vec<_> = iter.map(|(k, _)| k).collect();
+    let expected: Vec<_> = (0..50).map(|i| format!("k{:02}", i).into_bytes()).collect();
+    assert_eq!(keys, expected);
+
+    // Without the limit, the readers of all the files are kept.
+    let unlimited_path = TempDir::new("_rust_rocksdb_test_max_open_files_unlimited").expect("");
+    let unlimited_db = open_with_50_ssts(&unlimited_path, -1);
+    for i in 0..50 {
+        assert!(unlimited_db.get(format!("k{:02}", i).as_bytes()).unwrap().is_some());
+    }
+    let unlimited_cf = unlimited_db.cf_handle("default").unwrap();
+    let unlimited = unlimited_db.get_table_readers_mem_cf(unlimited_cf).unwrap();
+    assert!(all * 2 < unlimited, "{} {}", all, unlimited);
+}
+
+#[test]
+fn test_manual_wal_flush() {
+    let path = TempDir::new("_rust_rocksdb_test_manual_wal_flush").expect("");
+    let db_dir = path.path().join("db");
+    let crash_dir = path.path().join("crash");
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.set_manual_wal_flush(true);
+    let db = DB::open(opts, db_dir.to_str().unwrap()).unwrap();
+    db.put(b"k1", b"v1").unwrap();
+    db.flush_wal(true).unwrap();
+    db.put(b"k2", b"v2").unwrap();
+
+    // Copying the files of the running db is what a crash leaves behind.
+    fs::create_dir(&crash_dir).unwrap();
+    for entry in fs::read_dir(&db_dir).unwrap() {
+        let entry = entry.unwrap();
+        fs::copy(entry.path(), crash_dir.join(entry.file_name())).unwrap();
+    }
+    drop(db);
+
+    let db = DB::open(Options::new(), crash_dir.to_str().unwrap()).unwrap();
+    assert_eq!(db.get(b"k1").unwrap().unwrap(), &b"v1"[..]);
+    assert!(db.get(b"k2").unwrap().is_none());
+    drop(db);
+
+    let db = DB::open(Options::new(), db_dir.to_str().unwrap()).unwrap();
+    assert_eq!(db.get(b"k2").unwrap().unwrap(), &b"v2"[..]);
+}
+
+#[test]
+fn test_open_locked_db() {
+    let path = TempDir::new("_rust_rocksdb_test_open_locked_db").expect("");
+    let path_str = path.path().to_str().unwrap();
+    let db = DB::open_default(path_str).unwrap();
+    let e = DB::open_default(path_str).err().unwrap();
+    // The exact error of RocksDB 9.10.0, with the time and thread that
+    // hold the lock in the middle.
+    let prefix = format!("{}IO error: lock hold by current process, acquire time ",
+                         DB_LOCKED_ERROR_PREFIX);
+    let suffix = format!(": {}/LOCK: No locks available", path_str);
+    assert!(e.starts_with(&prefix) && e.ends_with(&suffix), "{}", e);
+    let holder: Vec<_> = e[prefix.len()..e.len() - suffix.len()]
+        .split(" acquiring thread ")
+        .collect();
+    assert_eq!(holder.len(), 2, "{}", e);
+    assert!(holder.iter().all(|n| n.parse::<u64>().is_ok()), "{}", e);
+    drop(db);
+
+    let mut opts = Options::new();
+    opts.set_skip_checking_sst_file_sizes_on_db_open(true);
+    let db = DB::open(opts, path_str).unwrap();
+    db.put(b"k1", b"v1").unwrap();
+    db.flush(true).unwrap();
+    drop(db);
+    let mut opts = Options::new();
+    opts.set_skip_checking_sst_file_sizes_on_db_open(true);
+    let db = DB::open(opts, path_str).unwrap();
+    assert_eq!(db.get(b"k1").unwrap().unwrap(), &b"v1"[..]);
+}
+
+const LOCK_DB_ENV: &str = "_RUST_ROCKSDB_TEST_LOCK_DB";
+
+#[test]
+fn test_open_db_locked_by_other_process() {
+    if let Ok(path) = env::var(LOCK_DB_ENV) {
+        // Run by the parent test, hold the db until the stdin is closed.
+        let _db = DB::open_default(&path).unwrap();
+        println!("locked");
+        std::io::stdin().read_line(&mut String::new()).unwrap();
+        return;
+    }
+
+    let path = TempDir::new("_rust_rocksdb_test_open_db_locked_by_other_process").expect("");
+    let path_str = path.path().to_str().unwrap();
+    let mut child = Command::new(env::current_exe().unwrap())
+        .args(&["--exact",
+                "test_rocksdb_options::test_open_db_locked_by_other_process",
+                "--nocapture"])
+        .env(LOCK_DB_ENV, path_str)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap()).lines();
+    assert!(stdout.any(|l| l.unwrap() == "locked"));
+
+    let e = DB::open_default(path_str).err().unwrap();
+    // The exact error of RocksDB 9.10.0, ending with the error of fcntl.
+    let prefix = format!("{}IO error: While lock file: {}/LOCK: ",
+                         DB_LOCKED_ERROR_PREFIX,
+                         path_str);
+    assert!(e.starts_with(&prefix), "{}", e);
+    if cfg!(target_os = "linux") {
+        assert_eq!(&e[prefix.len()..], "Resource temporarily unavailable");
+    }
+
+    drop(child.stdin.take());
+    // Keep reading so the child doesn't fail to print its test result.
+    stdout.for_each(drop);
+    assert!(child.wait().unwrap().success());
+    DB::open_default(path_str).unwrap();
+}
@@ -0,0 +1,146 @@
+use rocksdb::{DB, DBIterator, Options, ReadOptions, SeekKey, Writable, WriteBatch};
+use rocksdb::comparator::{self, encode_u64_ts};
+use std::os::raw::c_char;
+use std::ptr;
+use tempdir::TempDir;
+
+fn open_ts_db(path: &TempDir) -> DB {
+    let mut opts = Options::new();
+    opts.create_if_missing(true);
+    opts.set_comparator_with_u64_ts();
+    DB::open(opts, path.path().to_str().unwrap()).unwrap()
+}
+
+fn read_at(ts: u64) -> ReadOptions {
+    let mut opts = ReadOptions::new();
+    opts.set_timestamp(ts);
+    opts
+}
+
+fn scan(db: &DB, readopts: ReadOptions) -> Vec<(Vec<u8>, Vec<u8>, u64)> {
+    let cf = db.cf_handle("default").unwrap();
+    let mut iter = DBIterator::new_cf(db, cf, readopts);
+    let mut res = vec![];
+    iter.seek(SeekKey::Start);
+    while iter.valid() {
+        res.push((iter.key().to_vec(), iter.value().to_vec(), iter.timestamp().unwrap()));
+        iter.next();
+    }
+    res
+}
+
+#[test]
+fn test_user_timestamp_versions() {
+    let path = TempDir::new("_rust_rocksdb_test_user_timestamp_versions").expect("");
+    let db = open_ts_db(&path);
+    let cf = db.cf_handle("default").unwrap();
+    db.put_cf_with_ts(cf, b"k1", 1, b"v1").unwrap();
+    let batch = WriteBatch::new();
+    batch.put_cf_with_ts(cf, b"k1", 2, b"v2").unwrap();
+    batch.put_cf_with_ts(cf, b"k1", 3, b"v3").unwrap();
+    db.write(batch).unwrap();
+
+    let (v, ts) = db.get_cf_with_ts(cf, b"k1", &read_at(2)).unwrap().unwrap();
+    assert_eq!(&*v, b"v2");
+    assert_eq!(ts, 2);
+    let (v, ts) = db.get_cf_with_ts(cf, b"k1", &read_at(10)).unwrap().unwrap();
+    assert_eq!(&*v, b"v3");
+    assert_eq!(ts, 3);
+    assert!(db.get_cf_with_ts(cf, b"k1", &read_at(0)).unwrap().is_none());
+
+    assert_eq!(scan(&db, read_at(1)), vec![(b"k1".to_vec(), b"v1".to_vec(), 1)]);
+
+    // All the versions between the start ts and the read ts, newest first.
+    let mut readopts = read_at(3);
+    readopts.set_iter_start_ts(1);
+    assert_eq!(scan(&db, readopts),
+               vec![(b"k1".to_vec(), b"v3".to_vec(), 3),
+                    (b"k1".to_vec(), b"v2".to_vec(), 2),
+                    (b"k1".to_vec(), b"v1".to_vec(), 1)]);
+}
+
+#[test]
+fn test_user_timestamp_mismatch() {
+    let path = TempDir::new("_rust_rocksdb_test_user_timestamp_mismatch").expect("");
+    let db = open_ts_db(&path);
+    let cf = db.cf_handle("default").unwrap();
+    db.put_cf_with_ts(cf, b"k1", 1, b"v1").unwrap();
+
+    // A timestamped column family rejects the operations without one.
+    assert!(db.put_cf(cf, b"k2", b"v2").is_err());
+    assert!(db.get_cf(cf, b"k1").is_err());
+
+    // And the other column families reject the ones with a timestamp.
+    let path = TempDir::new("_rust_rocksdb_test_user_timestamp_mismatch_plain").expect("");
+    let db = DB::open_default(path.path().to_str().unwrap()).unwrap();
+    let cf = db.cf_handle("default").unwrap();
+    assert!(db.put_cf_with_ts(cf, b"k1", 1, b"v1").is_err());
+    let batch = WriteBatch::new();
+    assert!(batch.put_cf_with_ts(cf, b"k1", 1, b"v1").is_err());
+    assert!(batch.is_empty());
+    db.put_cf(cf, b"k1", b"v1").unwrap();
+    assert!(db.get_cf_with_ts(cf, b"k1", &read_at(1)).is_err());
+}
+
+#[test]
+fn test_user_timestamp_compare_short_keys() {
+    let compare = |a: &[u8], b: &[u8]| {
+        comparator::u64_ts_compare_callback(ptr::null_mut(),
+                                            a.as_ptr() as *const c_char,
+                                            a.len(),
+                                            b.as_ptr() as *const c_char,
+                                            b.len())
+    };
+    let mut k1 = b"k1".to_vec();
+    k1.extend_from_slice(&encode_u64_ts(1));
+    // Keys shorter than a timestamp are compared without one.
+    assert_eq!(compare(b"k", b"k"), 0);
+    assert_eq!(compare(b"k", &k1), -1);
+    assert_eq!(compare(&k1, b"k"), 1);
+    let compare_ts = |a: &[u8], b: &[u8]| {
+        comparator::u64_ts_compare_ts_callback(ptr::null_mut(),
+                                               a.as_ptr() as *const c_char,
+                                               a.len(),
+                                               b.as_ptr() as *const c_char,
+                                               b.len())
+    };
+    assert_eq!(compare_ts(b"", &encode_u64_ts(0)), 0);
+    assert_eq!(compare_ts(b"1", &encode_u64_ts(1)), -1);
+    let compare_without_ts = |a: &[u8], b: &[u8]| {
+        comparator::u64_ts_compare_without_ts_callback(ptr::null_mut(),
+                                                       a.as_ptr() as *const c_char,
+                                                       a.len(),
+                                                       true,
+                                                       b.as_ptr() as *const c_char,
+                                                       b.len(),
+                                                       true)
+    };
+    assert_eq!(compare_without_ts(b"k", b"k"), 0);
+    assert_eq!(compare_without_ts(&k1, b"k1"), 0);
+}
+
+#[test]
+fn test_user_timestamp_full_history_ts_low() {
+    let path = TempDir::new("_rust_rocksdb_test_user_timestamp_full_history_ts_low")
+        .expect("");
+    let db = open_ts_db(&path);
+    let cf = db.cf_handle("default").unwrap();
+    for ts in 1..4 {
+        db.put_cf_with_ts(cf, b"k1", ts, format!("v{}", ts).as_bytes()).unwrap();
+    }
+    db.flush(true).unwrap();
+    db.increase_full_history_ts_low(cf, 2).unwrap();
+    // The full history ts low can't go backward.
+    assert!(db.increase_full_history_ts_low(cf, 1).is_err());
+    db.compact_range(None, None);
+
+    // The versions visible at or above the full history ts low are kept.
+    let (v, ts) = db.get_cf_with_ts(cf, b"k1", &read_at(2)).unwrap().unwrap();
+    assert_eq!(&*v, b"v2");
+    assert_eq!(ts, 2);
+    let (v, ts) = db.get_cf_with_ts(cf, b"k1", &read_at(3)).unwrap().unwrap();
+    assert_eq!(&*v, b"v3");
+    assert_eq!(ts, 3);
+    // Reading below it is rejected.
+    assert!(db.get_cf_with_ts(cf, b"k1", &read_at(1)).is_err());
+}
@@ -0,0 +1,11 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Return the paths of the sst files in `dir`.
+pub fn sst_files(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("sst"))
+        .collect()
+}